@@ -0,0 +1,141 @@
+use {crate::copy::quote_ident, tokio_postgres::types::ToSql};
+
+#[cfg(test)]
+mod tests;
+
+/// A partial update of a row.
+///
+/// This trait is usually derived with the [`Changeset`](macro@crate::Changeset) proc macro.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres::{Client, Error};
+/// # use tokio_postgres_extractor::Changeset;
+/// #[derive(Changeset)]
+/// struct UserChanges {
+///     #[column(key)]
+///     id: i32,
+///     name: Option<String>,
+///     email: Option<Option<String>>,
+/// }
+///
+/// let changes = UserChanges {
+///     id: 1,
+///     name: None,
+///     email: Some(None),
+/// };
+/// assert_eq!(
+///     changes.update_sql("users").unwrap(),
+///     r#"UPDATE users SET "email" = $1 WHERE "id" = $2"#,
+/// );
+///
+/// async fn update_user(client: &Client, changes: &UserChanges) -> Result<u64, Error> {
+///     match changes.update_sql("users") {
+///         Some(sql) => client.execute(&sql, &changes.params()).await,
+///         None => Ok(0),
+///     }
+/// }
+/// ```
+pub trait Changeset {
+    /// The names of the columns that identify the row.
+    const KEY_COLUMNS: &'static [&'static str];
+
+    /// Returns the names and new values of the changed columns.
+    fn changes(&self) -> Vec<(&'static str, &(dyn ToSql + Sync))>;
+
+    /// Returns the values of the columns in [`Changeset::KEY_COLUMNS`].
+    fn keys(&self) -> Vec<&(dyn ToSql + Sync)>;
+
+    /// Returns whether no columns are changed.
+    fn is_empty(&self) -> bool {
+        self.changes().is_empty()
+    }
+
+    /// Returns an `UPDATE` statement for the table or `None` if no columns are changed.
+    ///
+    /// `table` is inserted verbatim and can therefore contain a schema. The parameters of
+    /// the statement are returned by [`Changeset::params`].
+    fn update_sql(&self, table: &str) -> Option<String> {
+        let changes = self.changes();
+        if changes.is_empty() {
+            return None;
+        }
+        let set: Vec<_> = changes
+            .iter()
+            .enumerate()
+            .map(|(idx, (name, _))| format!("{} = ${}", quote_ident(name), idx + 1))
+            .collect();
+        let filter: Vec<_> = Self::KEY_COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| format!("{} = ${}", quote_ident(name), changes.len() + idx + 1))
+            .collect();
+        Some(format!(
+            "UPDATE {} SET {} WHERE {}",
+            table,
+            set.join(", "),
+            filter.join(" AND "),
+        ))
+    }
+
+    /// Returns the parameters of the statement returned by [`Changeset::update_sql`].
+    fn params(&self) -> Vec<&(dyn ToSql + Sync)> {
+        let mut params: Vec<_> = self.changes().into_iter().map(|(_, v)| v).collect();
+        params.extend(self.keys());
+        params
+    }
+}
+
+/// A [`Changeset`] that can be computed from two versions of a row.
+///
+/// This trait is usually derived with the [`Changeset`](macro@crate::Changeset) proc macro
+/// and the `#[changeset(diff = T)]` attribute.
+pub trait Diff<T>: Changeset {
+    /// Returns the changes required to turn `old` into `new`.
+    ///
+    /// The key is taken from `old`.
+    fn diff(old: &T, new: &T) -> Self;
+}
+
+/// Returns the changes required to turn `old` into `new`.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::{diff, Changeset, Columns, Extract};
+/// #[derive(Clone, Columns, Extract)]
+/// struct User {
+///     id: i32,
+///     name: String,
+///     email: Option<String>,
+/// }
+///
+/// #[derive(Changeset)]
+/// #[changeset(diff = User)]
+/// struct UserChanges {
+///     #[column(key)]
+///     id: i32,
+///     name: Option<String>,
+///     email: Option<Option<String>>,
+/// }
+///
+/// let old = User {
+///     id: 1,
+///     name: "a".to_string(),
+///     email: None,
+/// };
+/// let mut new = old.clone();
+/// new.name = "b".to_string();
+/// let changes: UserChanges = diff(&old, &new);
+/// assert_eq!(
+///     changes.update_sql("users").unwrap(),
+///     r#"UPDATE users SET "name" = $1 WHERE "id" = $2"#,
+/// );
+/// ```
+pub fn diff<T, C>(old: &T, new: &T) -> C
+where
+    C: Diff<T>,
+{
+    C::diff(old, new)
+}
@@ -0,0 +1,131 @@
+use crate::{diff, tests::connect, Changeset, Columns, Extract};
+
+#[derive(Clone, Debug, PartialEq, Columns, Extract)]
+struct Item {
+    id: i32,
+    #[column(name = "Name")]
+    name: String,
+    note: Option<String>,
+    count: i64,
+}
+
+#[derive(Changeset)]
+#[changeset(diff = Item)]
+struct ItemChanges {
+    #[column(key)]
+    id: i32,
+    #[column(name = "Name")]
+    name: Option<String>,
+    note: Option<Option<String>>,
+    count: Option<i64>,
+}
+
+#[tokio::test]
+async fn update() {
+    let client = connect().await;
+    client
+        .batch_execute(
+            r#"create temporary table items (id int4 primary key, "Name" text, note text, count int8);
+               insert into items values (1, 'a', 'x', 1), (2, 'b', null, 2);"#,
+        )
+        .await
+        .unwrap();
+    let old = Item {
+        id: 1,
+        name: "a".to_string(),
+        note: Some("x".to_string()),
+        count: 1,
+    };
+    let mut new = old.clone();
+    new.note = None;
+    new.count = 3;
+    let changes: ItemChanges = diff(&old, &new);
+    assert!(changes.name.is_none());
+    let sql = changes.update_sql("items").unwrap();
+    assert_eq!(
+        sql,
+        r#"UPDATE items SET "note" = $1, "count" = $2 WHERE "id" = $3"#
+    );
+    let updated = client.execute(&sql, &changes.params()).await.unwrap();
+    assert_eq!(updated, 1);
+    let rows = client
+        .query("select * from items order by id", &[])
+        .await
+        .unwrap();
+    assert_eq!(Item::extract_once(&rows[0]), new);
+    assert_eq!(Item::extract_once(&rows[1]).count, 2);
+}
+
+#[test]
+fn unchanged() {
+    let old = Item {
+        id: 1,
+        name: "a".to_string(),
+        note: None,
+        count: 1,
+    };
+    let changes: ItemChanges = diff(&old, &old.clone());
+    assert!(changes.is_empty());
+    assert_eq!(changes.update_sql("items"), None);
+}
+
+#[test]
+fn rename_all() {
+    #[derive(Clone, Columns, Extract)]
+    #[columns(rename_all = "camelCase")]
+    struct Account {
+        account_id: i32,
+        display_name: String,
+        #[column(name = "EMail")]
+        email: String,
+    }
+
+    #[derive(Changeset)]
+    #[columns(rename_all = "camelCase")]
+    #[changeset(diff = Account)]
+    struct AccountChanges {
+        #[column(key)]
+        account_id: i32,
+        display_name: Option<String>,
+        #[column(name = "EMail")]
+        email: Option<String>,
+    }
+
+    let old = Account {
+        account_id: 1,
+        display_name: "a".to_string(),
+        email: "x".to_string(),
+    };
+    let mut new = old.clone();
+    new.display_name = "b".to_string();
+    new.email = "y".to_string();
+    let changes: AccountChanges = diff(&old, &new);
+    assert_eq!(AccountChanges::KEY_COLUMNS, ["accountId"]);
+    assert_eq!(
+        changes.update_sql("accounts").unwrap(),
+        r#"UPDATE accounts SET "displayName" = $1, "EMail" = $2 WHERE "accountId" = $3"#
+    );
+}
+
+#[test]
+fn primary_key() {
+    #[derive(Changeset)]
+    struct UserChanges {
+        #[column(primary_key)]
+        id: i32,
+        #[column(key)]
+        tenant: i32,
+        name: Option<String>,
+    }
+
+    let changes = UserChanges {
+        id: 1,
+        tenant: 2,
+        name: Some("a".to_string()),
+    };
+    assert_eq!(UserChanges::KEY_COLUMNS, ["id", "tenant"]);
+    assert_eq!(
+        changes.update_sql("users").unwrap(),
+        r#"UPDATE users SET "name" = $1 WHERE "id" = $2 AND "tenant" = $3"#
+    );
+}
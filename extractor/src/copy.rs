@@ -0,0 +1,298 @@
+//! Extraction from and insertion into binary `COPY` streams.
+//!
+//! `COPY ... TO STDOUT` and `COPY ... FROM STDIN` are the fastest ways to export and import
+//! large numbers of rows. Their binary format does not contain the names of the columns.
+//! Types implementing [`CopyOut`] and [`CopyIn`] know the names and types of their columns
+//! and can therefore build the `COPY` statements themselves.
+//!
+//! # Examples
+//!
+//! ```
+//! # use futures_util::TryStreamExt;
+//! # use tokio_postgres::Client;
+//! # use tokio_postgres_extractor::{CopyOut, Error};
+//! # use tokio_postgres_extractor::copy::{copy_out_statement, CopyOutExtractExt};
+//! #[derive(CopyOut)]
+//! struct User {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! async fn export_users(client: &Client) -> Result<Vec<User>, Error> {
+//!     let statement = copy_out_statement::<User>("from users");
+//!     let stream = client.copy_out(&statement).await?;
+//!     stream.extract::<User>().try_collect().await
+//! }
+//! ```
+//!
+//! ```
+//! # use tokio_postgres::Client;
+//! # use tokio_postgres_extractor::{CopyIn, Error};
+//! # use tokio_postgres_extractor::copy::{copy_in_statement, CopyInSink};
+//! #[derive(CopyIn)]
+//! struct User {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! async fn import_users(client: &Client, users: &[User]) -> Result<u64, Error> {
+//!     let sink = client.copy_in(&copy_in_statement::<User>("users")).await?;
+//!     let mut sink = CopyInSink::<User>::new(sink);
+//!     sink.write_all(users).await?;
+//!     sink.finish().await
+//! }
+//! ```
+
+use {
+    crate::{copy::sealed::Sealed, csv::CsvExtractStream, Error, ExtractText},
+    bytes::Bytes,
+    futures_core::Stream,
+    pin_project::pin_project,
+    std::{
+        borrow::Borrow,
+        fmt::Write,
+        future::poll_fn,
+        marker::PhantomData,
+        pin::{pin, Pin},
+        task::{Context, Poll},
+    },
+    tokio_postgres::{
+        binary_copy::{BinaryCopyInWriter, BinaryCopyOutRow, BinaryCopyOutStream},
+        types::{ToSql, Type},
+        CopyOutStream,
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A type that can be extracted from the rows of a binary `COPY ... TO STDOUT` stream.
+///
+/// This trait is usually derived with the [`CopyOut`](macro@crate::CopyOut) proc macro.
+pub trait CopyOut: Sized {
+    /// The names of the columns, in the order in which they appear in the stream.
+    const COLUMN_NAMES: &'static [&'static str];
+
+    /// Returns the types of the columns, in the order in which they appear in the stream.
+    fn column_types() -> Vec<Type>;
+
+    /// Extracts an instance of the type from a row of the stream.
+    fn extract_copy_out(row: &BinaryCopyOutRow) -> Result<Self, Error>;
+}
+
+/// Returns a `COPY (SELECT ...) TO STDOUT` statement that produces the columns of `T`.
+///
+/// The select list contains the columns of `T` cast to their types. `query` is appended
+/// to the select list and usually starts with `FROM`.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::CopyOut;
+/// # use tokio_postgres_extractor::copy::copy_out_statement;
+/// #[derive(CopyOut)]
+/// struct User {
+///     id: i32,
+///     name: String,
+/// }
+///
+/// assert_eq!(
+///     copy_out_statement::<User>("from users"),
+///     r#"COPY (SELECT "id"::"pg_catalog"."int4", "name"::"pg_catalog"."text" from users) TO STDOUT (FORMAT binary)"#,
+/// );
+/// ```
+pub fn copy_out_statement<T: CopyOut>(query: &str) -> String {
+    let mut res = "COPY (SELECT ".to_string();
+    for (idx, (name, ty)) in T::COLUMN_NAMES.iter().zip(T::column_types()).enumerate() {
+        if idx > 0 {
+            res.push_str(", ");
+        }
+        let _ = write!(
+            res,
+            "{}::{}.{}",
+            quote_ident(name),
+            quote_ident(ty.schema()),
+            quote_ident(ty.name()),
+        );
+    }
+    let _ = write!(res, " {}) TO STDOUT (FORMAT binary)", query);
+    res
+}
+
+/// A type that can be written to a binary `COPY ... FROM STDIN` stream.
+///
+/// This trait is usually derived with the [`CopyIn`](macro@crate::CopyIn) proc macro.
+pub trait CopyIn {
+    /// The names of the columns, in the order in which they appear in the stream.
+    const COLUMN_NAMES: &'static [&'static str];
+
+    /// Returns the types of the columns, in the order in which they appear in the stream.
+    fn column_types() -> Vec<Type>;
+
+    /// Returns the values of the columns, in the order in which they appear in the stream.
+    fn copy_in_values(&self) -> Vec<&(dyn ToSql + Sync)>;
+}
+
+/// Returns a `COPY ... FROM STDIN` statement that consumes the columns of `T`.
+///
+/// `table` is inserted verbatim and can therefore contain a schema.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::CopyIn;
+/// # use tokio_postgres_extractor::copy::copy_in_statement;
+/// #[derive(CopyIn)]
+/// struct User {
+///     id: i32,
+///     name: String,
+/// }
+///
+/// assert_eq!(
+///     copy_in_statement::<User>("public.users"),
+///     r#"COPY public.users ("id", "name") FROM STDIN (FORMAT binary)"#,
+/// );
+/// ```
+pub fn copy_in_statement<T: CopyIn>(table: &str) -> String {
+    let mut res = format!("COPY {} (", table);
+    for (idx, name) in T::COLUMN_NAMES.iter().enumerate() {
+        if idx > 0 {
+            res.push_str(", ");
+        }
+        res.push_str(&quote_ident(name));
+    }
+    res.push_str(") FROM STDIN (FORMAT binary)");
+    res
+}
+
+pub(crate) fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// A [`Stream`] producing `T`s from a binary [`CopyOutStream`].
+///
+/// Construct it using [`CopyOutExtractExt::extract`].
+#[pin_project]
+pub struct CopyOutExtractStream<T> {
+    /// The underlying stream.
+    ///
+    /// This field is public for easier access.
+    #[pin]
+    pub stream: BinaryCopyOutStream,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Stream for CopyOutExtractStream<T>
+where
+    T: CopyOut,
+{
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().stream.poll_next(cx).map(|row| {
+            row.map(|row| match row {
+                Ok(row) => T::extract_copy_out(&row),
+                Err(e) => Err(e.into()),
+            })
+        })
+    }
+}
+
+/// Extension trait for extracting from a [`CopyOutStream`].
+pub trait CopyOutExtractExt: Sealed {
+    /// Turns the stream into a stream over `T`.
+    ///
+    /// The stream must have been created with the statement returned by
+    /// [`copy_out_statement::<T>`](copy_out_statement) or a statement that produces the
+    /// same columns in binary format.
+    fn extract<T: CopyOut>(self) -> CopyOutExtractStream<T>;
+
+    /// Turns the stream into a stream over `T`, parsing the output as CSV.
+    ///
+    /// The stream must have been created with a statement that produces CSV with a header
+    /// line, e.g., `COPY ... TO STDOUT (FORMAT csv, HEADER)`. See the [`csv`](crate::csv)
+    /// module for details.
+    fn extract_csv<T: ExtractText>(self) -> CsvExtractStream<T, CopyOutStream>;
+}
+
+impl Sealed for CopyOutStream {}
+
+impl CopyOutExtractExt for CopyOutStream {
+    fn extract<T: CopyOut>(self) -> CopyOutExtractStream<T> {
+        CopyOutExtractStream {
+            stream: BinaryCopyOutStream::new(self, &T::column_types()),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn extract_csv<T: ExtractText>(self) -> CsvExtractStream<T, CopyOutStream> {
+        CsvExtractStream::new(self)
+    }
+}
+
+/// A writer of `T`s to a binary [`CopyInSink`](tokio_postgres::CopyInSink).
+///
+/// The rows are buffered and sent to the server in batches of a few kilobytes. The copy
+/// must be completed with [`CopyInSink::finish`]. Otherwise it is aborted when the writer
+/// is dropped.
+pub struct CopyInSink<T> {
+    writer: Pin<Box<BinaryCopyInWriter>>,
+    _phantom: PhantomData<fn(&T)>,
+}
+
+impl<T> CopyInSink<T>
+where
+    T: CopyIn,
+{
+    /// Creates a new writer.
+    ///
+    /// The sink must have been created with the statement returned by
+    /// [`copy_in_statement::<T>`](copy_in_statement) or a statement that consumes the same
+    /// columns in binary format.
+    pub fn new(sink: tokio_postgres::CopyInSink<Bytes>) -> Self {
+        Self {
+            writer: Box::pin(BinaryCopyInWriter::new(sink, &T::column_types())),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Writes a single row.
+    pub async fn write(&mut self, value: &T) -> Result<(), Error> {
+        self.writer.as_mut().write(&value.copy_in_values()).await?;
+        Ok(())
+    }
+
+    /// Writes all rows produced by an iterator.
+    pub async fn write_all<I>(&mut self, values: I) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        for value in values {
+            self.write(value.borrow()).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes all rows produced by a stream.
+    pub async fn write_stream<S>(&mut self, values: S) -> Result<(), Error>
+    where
+        S: Stream,
+        S::Item: Borrow<T>,
+    {
+        let mut values = pin!(values);
+        while let Some(value) = poll_fn(|cx| values.as_mut().poll_next(cx)).await {
+            self.write(value.borrow()).await?;
+        }
+        Ok(())
+    }
+
+    /// Completes the copy, returning the number of rows added.
+    pub async fn finish(mut self) -> Result<u64, Error> {
+        Ok(self.writer.as_mut().finish().await?)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
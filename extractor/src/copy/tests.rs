@@ -0,0 +1,128 @@
+use {
+    crate::{
+        copy::{copy_in_statement, copy_out_statement, CopyInSink, CopyOutExtractExt},
+        tests::connect,
+        CopyIn, CopyOut, Error,
+    },
+    futures_util::{stream, TryStreamExt},
+    tokio_postgres::types::Type,
+};
+
+#[tokio::test]
+async fn copy_out() {
+    #[derive(Debug, PartialEq, CopyOut)]
+    struct X {
+        id: i64,
+        #[column(name = "Name")]
+        name: Option<String>,
+        #[column(null_default = -1)]
+        score: i32,
+        #[column(via = i32)]
+        small: u8,
+        #[column(pattern = "q{}", range = 1..=2)]
+        q: [f64; 2],
+        #[column(skip, default = 7)]
+        seven: i32,
+        #[column(sql_type = Type::VARCHAR)]
+        raw: String,
+    }
+
+    assert_eq!(
+        X::COLUMN_NAMES,
+        ["id", "Name", "score", "small", "q1", "q2", "raw"]
+    );
+
+    let client = connect().await;
+    let statement = copy_out_statement::<X>(
+        r#"from (
+            select i id, nullif('n' || i, 'n2') "Name", nullif(i, 1) score, i small,
+                   i / 2 q1, 0.5 q2, 'x' raw
+            from generate_series(1, 2) i
+        ) t"#,
+    );
+    let stream = client.copy_out(&statement).await.unwrap();
+    let rows: Vec<X> = stream.extract().try_collect().await.unwrap();
+    assert_eq!(
+        rows,
+        [
+            X {
+                id: 1,
+                name: Some("n1".to_string()),
+                score: -1,
+                small: 1,
+                q: [0.0, 0.5],
+                seven: 7,
+                raw: "x".to_string(),
+            },
+            X {
+                id: 2,
+                name: None,
+                score: 2,
+                small: 2,
+                q: [1.0, 0.5],
+                seven: 7,
+                raw: "x".to_string(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn copy_out_error() {
+    #[derive(Debug, CopyOut)]
+    struct X {
+        #[allow(dead_code)]
+        #[column(via = i32)]
+        small: u8,
+    }
+
+    let client = connect().await;
+    let statement = copy_out_statement::<X>("from (select 1000 small) t");
+    let stream = client.copy_out(&statement).await.unwrap();
+    let res: Result<Vec<X>, _> = stream.extract().try_collect().await;
+    assert!(matches!(res, Err(Error::Conversion { column: 0, .. })));
+}
+
+#[tokio::test]
+async fn copy_in() {
+    #[derive(Debug, PartialEq, CopyIn, CopyOut)]
+    struct X {
+        id: i32,
+        #[column(name = "Name")]
+        name: Option<String>,
+        #[column(pattern = "q{}", range = 1..=2)]
+        q: [i64; 2],
+        #[column(skip)]
+        skipped: bool,
+    }
+
+    let client = connect().await;
+    client
+        .batch_execute(
+            r#"create temporary table copy_in (id int4, "Name" text, q1 int8, q2 int8, extra text)"#,
+        )
+        .await
+        .unwrap();
+    let x = |id: i32| X {
+        id,
+        name: (id % 2 == 0).then(|| format!("n{}", id)),
+        q: [id as i64, -(id as i64)],
+        skipped: false,
+    };
+    let sink = client
+        .copy_in(&copy_in_statement::<X>("copy_in"))
+        .await
+        .unwrap();
+    let mut sink = CopyInSink::new(sink);
+    sink.write(&x(0)).await.unwrap();
+    sink.write_all((1..1000).map(x)).await.unwrap();
+    sink.write_stream(stream::iter((1000..2000).map(x)))
+        .await
+        .unwrap();
+    assert_eq!(sink.finish().await.unwrap(), 2000);
+
+    let statement = copy_out_statement::<X>("from copy_in order by id");
+    let stream = client.copy_out(&statement).await.unwrap();
+    let rows: Vec<X> = stream.extract().try_collect().await.unwrap();
+    assert_eq!(rows, (0..2000).map(x).collect::<Vec<_>>());
+}
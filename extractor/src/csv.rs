@@ -0,0 +1,437 @@
+//! Extraction from CSV inputs.
+//!
+//! `COPY ... TO STDOUT (FORMAT csv, HEADER)` and `psql`'s `\copy` produce CSV data whose
+//! first line contains the names of the columns. [`CsvExtractStream`] uses this header in
+//! the same way that [`ExtractText`] uses the columns of a
+//! [`SimpleQueryRow`](tokio_postgres::SimpleQueryRow): the mapping between fields and
+//! columns is computed once and each value is then parsed with
+//! [`FromStr`](std::str::FromStr).
+//!
+//! Unquoted empty values are `NULL`. Quoted empty values are empty strings. Empty lines
+//! are skipped unless the header contains a single column, in which case they are records
+//! whose value is `NULL`.
+//!
+//! # Examples
+//!
+//! ```
+//! # use futures_util::TryStreamExt;
+//! # use tokio_postgres::Client;
+//! # use tokio_postgres_extractor::{Columns, Error, ExtractText};
+//! # use tokio_postgres_extractor::copy::CopyOutExtractExt;
+//! # use tokio_postgres_extractor::csv::CsvExtractStream;
+//! #[derive(Columns, ExtractText)]
+//! struct User {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! async fn export_users(client: &Client) -> Result<Vec<User>, Error> {
+//!     let stream = client
+//!         .copy_out("copy users to stdout (format csv, header)")
+//!         .await?;
+//!     stream.extract_csv::<User>().try_collect().await
+//! }
+//!
+//! async fn import_users(file: impl tokio::io::AsyncRead) -> Result<Vec<User>, Error> {
+//!     CsvExtractStream::<User, _>::from_reader(file)
+//!         .try_collect()
+//!         .await
+//! }
+//! ```
+
+use {
+    crate::{
+        text::{sealed::Sealed, TextRow},
+        Error, ExtractText,
+    },
+    bytes::Bytes,
+    futures_core::Stream,
+    pin_project::pin_project,
+    std::{
+        mem,
+        ops::Range,
+        pin::Pin,
+        sync::Arc,
+        task::{ready, Context, Poll},
+    },
+    tokio::io::{AsyncRead, ReadBuf},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A [`Stream`] producing `T`s from a CSV input with a header line.
+///
+/// The input is provided by a stream of byte chunks such as a
+/// [`CopyOutStream`](tokio_postgres::CopyOutStream). Construct it using [`Self::new`],
+/// [`Self::from_reader`], or
+/// [`CopyOutExtractExt::extract_csv`](crate::copy::CopyOutExtractExt::extract_csv).
+///
+/// If the input is malformed or cannot be read, the stream produces an error and ends. This
+/// includes headers that do not contain a column for every field.
+#[pin_project]
+pub struct CsvExtractStream<T, S>
+where
+    T: ExtractText,
+{
+    /// The underlying stream.
+    ///
+    /// This field is public for easier access.
+    #[pin]
+    pub stream: S,
+    chunk: Option<Bytes>,
+    decoder: Decoder,
+    columns: Option<T::Columns>,
+    done: bool,
+}
+
+impl<T, S, E> CsvExtractStream<T, S>
+where
+    T: ExtractText,
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<Error>,
+{
+    /// Creates a stream that reads the CSV input from a stream of byte chunks.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            chunk: None,
+            decoder: Decoder::default(),
+            columns: None,
+            done: false,
+        }
+    }
+}
+
+impl<T, R> CsvExtractStream<T, ReadChunks<R>>
+where
+    T: ExtractText,
+    R: AsyncRead,
+{
+    /// Creates a stream that reads the CSV input from an [`AsyncRead`].
+    pub fn from_reader(reader: R) -> Self {
+        Self::new(ReadChunks {
+            reader,
+            buf: vec![0; 8192].into_boxed_slice(),
+        })
+    }
+}
+
+impl<T, S, E> Stream for CsvExtractStream<T, S>
+where
+    T: ExtractText,
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<Error>,
+{
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut slf = self.project();
+        loop {
+            if *slf.done {
+                return Poll::Ready(None);
+            }
+            let record = match slf.chunk {
+                Some(chunk) => {
+                    let (n, record) = slf.decoder.decode(&chunk[..]);
+                    if n == chunk.len() {
+                        *slf.chunk = None;
+                    } else {
+                        *chunk = chunk.slice(n..);
+                    }
+                    record
+                }
+                None => match ready!(slf.stream.as_mut().poll_next(cx)) {
+                    Some(Ok(chunk)) => {
+                        *slf.chunk = Some(chunk);
+                        continue;
+                    }
+                    Some(Err(e)) => Some(Err(e.into())),
+                    None => {
+                        *slf.done = true;
+                        slf.decoder.finish()
+                    }
+                },
+            };
+            if slf.columns.is_none() {
+                if let Some(header) = slf.decoder.header() {
+                    match T::try_text_columns(&header) {
+                        Ok(columns) => *slf.columns = Some(columns),
+                        Err(e) => {
+                            *slf.done = true;
+                            let e = Error::Csv {
+                                line: slf.decoder.header_line,
+                                message: e.to_string(),
+                            };
+                            return Poll::Ready(Some(Err(e)));
+                        }
+                    }
+                }
+            }
+            match record {
+                Some(Ok(record)) => {
+                    return Poll::Ready(Some(T::extract_text(slf.columns, &record)));
+                }
+                Some(Err(e)) => {
+                    *slf.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// A [`Stream`] producing the chunks read from an [`AsyncRead`].
+///
+/// Construct it using [`CsvExtractStream::from_reader`].
+#[pin_project]
+pub struct ReadChunks<R> {
+    /// The underlying reader.
+    ///
+    /// This field is public for easier access.
+    #[pin]
+    pub reader: R,
+    buf: Box<[u8]>,
+}
+
+impl<R> Stream for ReadChunks<R>
+where
+    R: AsyncRead,
+{
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let slf = self.project();
+        let mut buf = ReadBuf::new(slf.buf);
+        ready!(slf.reader.poll_read(cx, &mut buf))?;
+        match buf.filled() {
+            [] => Poll::Ready(None),
+            filled => Poll::Ready(Some(Ok(Bytes::copy_from_slice(filled)))),
+        }
+    }
+}
+
+/// A record of a CSV input.
+struct Record {
+    columns: Arc<[String]>,
+    buf: String,
+    fields: Vec<Option<Range<usize>>>,
+}
+
+impl Sealed for Record {}
+
+impl TextRow for Record {
+    fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    fn column_name(&self, idx: usize) -> &str {
+        &self.columns[idx]
+    }
+
+    fn get(&self, idx: usize) -> Option<&str> {
+        self.fields[idx].clone().map(|range| &self.buf[range])
+    }
+}
+
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+enum State {
+    #[default]
+    RecordStart,
+    FieldStart,
+    Unquoted,
+    Quoted,
+    QuoteInQuoted,
+    CarriageReturn,
+}
+
+/// An incremental CSV parser.
+///
+/// The first record is interpreted as the header.
+#[derive(Default)]
+struct Decoder {
+    state: State,
+    /// The number of line feeds consumed so far.
+    line: u64,
+    /// The line in which the current record starts, starting at 1.
+    record_line: u64,
+    /// The line of the header, starting at 1.
+    header_line: u64,
+    columns: Option<Arc<[String]>>,
+    buf: Vec<u8>,
+    fields: Vec<Option<Range<usize>>>,
+    field_start: usize,
+}
+
+impl Decoder {
+    /// Parses `data` until the end of the next data record.
+    ///
+    /// Returns the number of bytes consumed.
+    fn decode(&mut self, data: &[u8]) -> (usize, Option<Result<Record, Error>>) {
+        let mut pos = 0;
+        while pos < data.len() {
+            let b = data[pos];
+            pos += 1;
+            if self.state == State::RecordStart {
+                self.record_line = self.line + 1;
+            }
+            if b == b'\n' {
+                self.line += 1;
+            }
+            let end = match (self.state, b) {
+                (State::CarriageReturn, b'\n') => {
+                    self.state = State::RecordStart;
+                    false
+                }
+                (State::CarriageReturn, _) => {
+                    self.state = State::RecordStart;
+                    pos -= 1;
+                    false
+                }
+                (State::RecordStart, b'\n' | b'\r') if self.skips_empty_lines() => {
+                    if b == b'\r' {
+                        self.state = State::CarriageReturn;
+                    }
+                    false
+                }
+                (State::RecordStart | State::FieldStart, b'"') => {
+                    self.field_start = self.buf.len();
+                    self.state = State::Quoted;
+                    false
+                }
+                (State::RecordStart | State::FieldStart, b',') => {
+                    self.fields.push(None);
+                    self.state = State::FieldStart;
+                    false
+                }
+                (State::RecordStart | State::FieldStart, b'\n' | b'\r') => {
+                    self.fields.push(None);
+                    self.end_line(b)
+                }
+                (State::RecordStart | State::FieldStart, _) => {
+                    self.field_start = self.buf.len();
+                    self.buf.push(b);
+                    self.state = State::Unquoted;
+                    false
+                }
+                (State::Unquoted | State::QuoteInQuoted, b',') => {
+                    self.push_field();
+                    self.state = State::FieldStart;
+                    false
+                }
+                (State::Unquoted | State::QuoteInQuoted, b'\n' | b'\r') => {
+                    self.push_field();
+                    self.end_line(b)
+                }
+                (State::Unquoted, _) => {
+                    self.buf.push(b);
+                    false
+                }
+                (State::Quoted, b'"') => {
+                    self.state = State::QuoteInQuoted;
+                    false
+                }
+                (State::Quoted, _) => {
+                    self.buf.push(b);
+                    false
+                }
+                (State::QuoteInQuoted, b'"') => {
+                    self.buf.push(b'"');
+                    self.state = State::Quoted;
+                    false
+                }
+                (State::QuoteInQuoted, _) => {
+                    return (
+                        pos,
+                        Some(Err(self.error("unexpected character after quote"))),
+                    );
+                }
+            };
+            if end {
+                if let Some(record) = self.end_record() {
+                    return (pos, Some(record));
+                }
+            }
+        }
+        (pos, None)
+    }
+
+    /// Completes the last record at the end of the input.
+    fn finish(&mut self) -> Option<Result<Record, Error>> {
+        match self.state {
+            State::RecordStart | State::CarriageReturn => return None,
+            State::FieldStart => self.fields.push(None),
+            State::Unquoted | State::QuoteInQuoted => self.push_field(),
+            State::Quoted => return Some(Err(self.error("unterminated quoted value"))),
+        }
+        self.end_record()
+    }
+
+    /// Returns a record without values that contains the columns of the header, if the
+    /// header has been parsed.
+    fn header(&self) -> Option<Record> {
+        Some(Record {
+            columns: self.columns.clone()?,
+            buf: String::new(),
+            fields: vec![],
+        })
+    }
+
+    /// Returns whether empty lines are skipped instead of being records with a single
+    /// `NULL` value.
+    fn skips_empty_lines(&self) -> bool {
+        !matches!(&self.columns, Some(columns) if columns.len() == 1)
+    }
+
+    fn push_field(&mut self) {
+        self.fields.push(Some(self.field_start..self.buf.len()));
+    }
+
+    fn end_line(&mut self, b: u8) -> bool {
+        self.state = match b {
+            b'\r' => State::CarriageReturn,
+            _ => State::RecordStart,
+        };
+        true
+    }
+
+    fn end_record(&mut self) -> Option<Result<Record, Error>> {
+        let buf = match String::from_utf8(mem::take(&mut self.buf)) {
+            Ok(buf) => buf,
+            Err(e) => return Some(Err(self.error(&e.to_string()))),
+        };
+        let fields = mem::take(&mut self.fields);
+        let columns = match &self.columns {
+            Some(columns) => columns.clone(),
+            None => {
+                let columns = fields
+                    .iter()
+                    .map(|field| match field {
+                        Some(range) => buf[range.clone()].to_string(),
+                        None => String::new(),
+                    })
+                    .collect();
+                self.columns = Some(columns);
+                self.header_line = self.record_line;
+                return None;
+            }
+        };
+        if fields.len() != columns.len() {
+            let msg = format!("expected {} values, found {}", columns.len(), fields.len());
+            return Some(Err(self.error(&msg)));
+        }
+        Some(Ok(Record {
+            columns,
+            buf,
+            fields,
+        }))
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::Csv {
+            line: self.record_line,
+            message: message.to_string(),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use {
+    crate::{copy::CopyOutExtractExt, csv::CsvExtractStream, tests::connect, Error},
+    bytes::Bytes,
+    futures_util::{stream, TryStreamExt},
+    tokio_postgres_extractor_macros::{Columns, ExtractText},
+};
+
+#[derive(Debug, PartialEq, Columns, ExtractText)]
+struct User {
+    id: i32,
+    name: Option<String>,
+    #[column(null_default)]
+    age: i64,
+}
+
+fn user(id: i32, name: Option<&str>, age: i64) -> User {
+    User {
+        id,
+        name: name.map(|n| n.to_string()),
+        age,
+    }
+}
+
+async fn parse(input: &str) -> Result<Vec<User>, Error> {
+    CsvExtractStream::from_reader(input.as_bytes())
+        .try_collect()
+        .await
+}
+
+#[tokio::test]
+async fn copy_out() {
+    let stream = connect()
+        .await
+        .copy_out(
+            "copy (
+                select * from (values
+                    (null::int8, 'a', 1),
+                    (3, E'b,\n\"c\"', 2),
+                    (4, '', 3),
+                    (5, null, 4)
+                ) t(age, name, id)
+            ) to stdout (format csv, header)",
+        )
+        .await
+        .unwrap();
+    let users: Vec<User> = stream.extract_csv().try_collect().await.unwrap();
+    assert_eq!(
+        users,
+        [
+            user(1, Some("a"), 0),
+            user(2, Some("b,\n\"c\""), 3),
+            user(3, Some(""), 4),
+            user(4, None, 5),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn reader() {
+    let users = parse("name,id,age\r\n\"x\"\"y\",1,\r\n,2,3").await.unwrap();
+    assert_eq!(users, [user(1, Some("x\"y"), 0), user(2, None, 3)]);
+}
+
+#[tokio::test]
+async fn chunks() {
+    let input = "id,name,age\n1,\"a\r\nb\",2\n";
+    let chunks = input
+        .bytes()
+        .map(|b| Ok::<_, Error>(Bytes::from(vec![b])))
+        .collect::<Vec<_>>();
+    let users: Vec<User> = CsvExtractStream::new(stream::iter(chunks))
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(users, [user(1, Some("a\r\nb"), 2)]);
+}
+
+#[tokio::test]
+async fn empty() {
+    assert_eq!(parse("").await.unwrap(), []);
+    assert_eq!(parse("id,name,age\n").await.unwrap(), []);
+}
+
+#[tokio::test]
+async fn empty_lines() {
+    let users = parse("\r\n\nid,name,age\n\n1,a,2\r\n\r\n\n2,,3\n\n")
+        .await
+        .unwrap();
+    assert_eq!(users, [user(1, Some("a"), 2), user(2, None, 3)]);
+
+    #[derive(Debug, PartialEq, Columns, ExtractText)]
+    struct Id {
+        id: Option<i32>,
+    }
+
+    let ids: Vec<Id> = CsvExtractStream::from_reader(&b"\nid\n1\n\n2\n"[..])
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        ids,
+        [Id { id: Some(1) }, Id { id: None }, Id { id: Some(2) }]
+    );
+}
+
+#[tokio::test]
+async fn missing_column() {
+    for (input, header_line) in [("id,age\n1,2\n", 1), ("\nid,age\n1,2\n", 2)] {
+        match parse(input).await {
+            Err(Error::Csv { line, message }) => {
+                assert_eq!(line, header_line);
+                assert_eq!(message, "There is no column named `name`");
+            }
+            res => panic!("{:?}", res),
+        }
+    }
+}
+
+#[tokio::test]
+async fn header_only() {
+    assert_eq!(parse("age,id,name\n").await.unwrap(), []);
+    for input in ["id,age\n", "id,age", "\n\nid,age\n\n"] {
+        let res = parse(input).await;
+        assert!(matches!(res, Err(Error::Csv { .. })), "{:?}", res);
+    }
+    let stream = connect()
+        .await
+        .copy_out("copy (select 1 id, 2 age where false) to stdout (format csv, header)")
+        .await
+        .unwrap();
+    let res: Result<Vec<User>, _> = stream.extract_csv().try_collect().await;
+    match res {
+        Err(Error::Csv { line: 1, message }) => {
+            assert_eq!(message, "There is no column named `name`");
+        }
+        res => panic!("{:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn malformed() {
+    let res = parse("id,name,age\n1,a,2\n2,\"b\nc\",3,4\n").await;
+    assert!(matches!(res, Err(Error::Csv { line: 3, .. })));
+    let res = parse("id,name,age\n1,\"a\"b,2\n").await;
+    assert!(matches!(res, Err(Error::Csv { line: 2, .. })));
+    let res = parse("id,name,age\n1,\"a,2\n").await;
+    assert!(matches!(res, Err(Error::Csv { line: 2, .. })));
+}
+
+#[tokio::test]
+async fn conversion() {
+    let mut stream = CsvExtractStream::<User, _>::from_reader(&b"id,name,age\nx,a,1\n2,b,3"[..]);
+    let res = stream.try_next().await;
+    assert!(matches!(res, Err(Error::Conversion { column: 0, .. })));
+    assert_eq!(
+        stream.try_next().await.unwrap(),
+        Some(user(2, Some("b"), 3))
+    );
+}
@@ -0,0 +1,73 @@
+use {
+    std::error::Error,
+    tokio_postgres::types::{FromSql, Kind, Type},
+};
+
+/// A dynamically typed Postgres value.
+///
+/// This type implements [`FromSql`] for all Postgres types and can therefore decode any
+/// column. Values of common types are decoded into the corresponding Rust types. All
+/// other values are stored in their binary representation.
+///
+/// This type is primarily useful for fields annotated with `#[column(rest)]`. See the
+/// documentation of the [`Columns`](macro@crate::Columns) proc macro.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DynValue {
+    /// A `NULL` value.
+    Null,
+    /// A `bool` value.
+    Bool(bool),
+    /// An `int2` value.
+    Int2(i16),
+    /// An `int4` value.
+    Int4(i32),
+    /// An `int8` value.
+    Int8(i64),
+    /// A `float4` value.
+    Float4(f32),
+    /// A `float8` value.
+    Float8(f64),
+    /// A `text`, `varchar`, `bpchar`, `name`, or enum value.
+    Text(String),
+    /// A `bytea` value.
+    Bytea(Vec<u8>),
+    /// A value of any other type.
+    Other {
+        /// The type of the value.
+        ty: Type,
+        /// The binary representation of the value.
+        raw: Vec<u8>,
+    },
+}
+
+impl<'a> FromSql<'a> for DynValue {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let value = match *ty {
+            Type::BOOL => DynValue::Bool(bool::from_sql(ty, raw)?),
+            Type::INT2 => DynValue::Int2(i16::from_sql(ty, raw)?),
+            Type::INT4 => DynValue::Int4(i32::from_sql(ty, raw)?),
+            Type::INT8 => DynValue::Int8(i64::from_sql(ty, raw)?),
+            Type::FLOAT4 => DynValue::Float4(f32::from_sql(ty, raw)?),
+            Type::FLOAT8 => DynValue::Float8(f64::from_sql(ty, raw)?),
+            Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::UNKNOWN => {
+                DynValue::Text(String::from_sql(ty, raw)?)
+            }
+            Type::BYTEA => DynValue::Bytea(Vec::from_sql(ty, raw)?),
+            _ if matches!(ty.kind(), Kind::Enum(_)) => DynValue::Text(String::from_sql(ty, raw)?),
+            _ => DynValue::Other {
+                ty: ty.clone(),
+                raw: raw.to_vec(),
+            },
+        };
+        Ok(value)
+    }
+
+    fn from_sql_null(_: &Type) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(DynValue::Null)
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    error::Error as StdError,
+    fmt::{Display, Formatter},
+    io,
+    sync::Arc,
+};
+
+/// The error type of this crate.
+///
+/// This error is returned by the fallible extraction functions such as
+/// [`Extract::try_extract_once`][crate::Extract::try_extract_once] as well as by the
+/// functions that execute statements, parse inputs, or encode values, for example, in the
+/// [`csv`](crate::csv), [`page`](crate::page), and [`table`](crate::table) modules. The
+/// documentation of each variant describes where it is produced.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error returned by `tokio_postgres`.
+    ///
+    /// This error is produced if a column could not be decoded or if a statement failed.
+    Postgres(tokio_postgres::Error),
+    /// A column was decoded but could not be converted to the type of the field.
+    ///
+    /// This error is produced by fields annotated with `#[column(via = Type)]` and by
+    /// values in the text format that cannot be parsed.
+    Conversion {
+        /// The index of the column.
+        column: usize,
+        /// The error returned by the conversion.
+        error: Box<dyn StdError + Send + Sync>,
+    },
+    /// The fields of a type could not be mapped to the columns of a row.
+    ///
+    /// This error is produced by [`Columns::try_columns`](crate::Columns::try_columns),
+    /// [`ExtractText::try_text_columns`](crate::ExtractText::try_text_columns), and
+    /// [`ChangeDecoder::decode`](crate::replication::ChangeDecoder::decode), for example if
+    /// a row has no column with the name of a field.
+    Mapping {
+        /// A description of the error.
+        message: String,
+    },
+    /// A column in the text format was `NULL` but the field is not nullable.
+    Null {
+        /// The index of the column.
+        column: usize,
+    },
+    /// A CSV input was malformed.
+    Csv {
+        /// The line of the input in which the malformed record starts, starting at 1.
+        line: u64,
+        /// A description of the error.
+        message: String,
+    },
+    /// The payload of a notification was malformed.
+    ///
+    /// This error is produced by the functions in the [`notification`](crate::notification)
+    /// module.
+    Payload {
+        /// A description of the error.
+        message: String,
+    },
+    /// A logical replication message was malformed or unsupported.
+    ///
+    /// This error is produced by the functions in the [`replication`](crate::replication)
+    /// module.
+    Replication {
+        /// A description of the error.
+        message: String,
+    },
+    /// A statement refers to a named parameter that is not a field of the parameter type.
+    ///
+    /// This error is produced by [`NamedQuery::parse`](crate::params::NamedQuery::parse).
+    UnknownParameter {
+        /// The name of the parameter.
+        name: String,
+    },
+    /// An input could not be read.
+    Io(io::Error),
+    /// The query loading a batch failed.
+    ///
+    /// This error is produced by [`BatchLoader`](crate::table::BatchLoader) and returned to
+    /// every caller waiting for the batch.
+    Batch(Arc<Error>),
+    /// A [`Cursor`](crate::page::Cursor) does not match the sort keys of the type.
+    InvalidCursor,
+    /// The extracted value was rejected by the validation function of the type.
+    ///
+    /// This error is produced by types annotated with `#[extract(validate = path)]`.
+    Validation {
+        /// The name of the type.
+        ty: &'static str,
+        /// The error returned by the validation function.
+        error: Box<dyn StdError + Send + Sync>,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Postgres(e) => Display::fmt(e, f),
+            Error::Conversion { column, error } => {
+                write!(f, "error converting column {}: {}", column, error)
+            }
+            Error::Mapping { message } => f.write_str(message),
+            Error::Null { column } => write!(f, "unexpected NULL in column {}", column),
+            Error::Csv { line, message } => write!(f, "invalid CSV in line {}: {}", line, message),
+            Error::Payload { message } => write!(f, "invalid notification payload: {}", message),
+            Error::Replication { message } => {
+                write!(f, "invalid replication message: {}", message)
+            }
+            Error::UnknownParameter { name } => write!(f, "unknown parameter `{}`", name),
+            Error::Io(e) => Display::fmt(e, f),
+            Error::Batch(e) => write!(f, "loading the batch failed: {}", e),
+            Error::InvalidCursor => f.write_str("the cursor does not match the sort keys"),
+            Error::Validation { ty, error } => {
+                write!(f, "validation of `{}` failed: {}", ty, error)
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Postgres(e) => Some(e),
+            Error::Conversion { error, .. } => Some(&**error),
+            Error::Mapping { .. } => None,
+            Error::Null { .. } => None,
+            Error::Csv { .. } => None,
+            Error::Payload { .. } => None,
+            Error::Replication { .. } => None,
+            Error::UnknownParameter { .. } => None,
+            Error::Io(e) => Some(e),
+            Error::Batch(e) => Some(&**e),
+            Error::InvalidCursor => None,
+            Error::Validation { error, .. } => Some(&**error),
+        }
+    }
+}
+
+impl From<tokio_postgres::Error> for Error {
+    fn from(e: tokio_postgres::Error) -> Self {
+        Error::Postgres(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,142 @@
+use {crate::copy::quote_ident, tokio_postgres::types::ToSql};
+
+#[cfg(test)]
+mod tests;
+
+/// A type that can be inserted into a table.
+///
+/// This trait is usually derived with the [`Insert`](macro@crate::Insert) proc macro.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres::{Client, Error};
+/// # use tokio_postgres_extractor::{Columns, Extract, Insert};
+/// #[derive(Columns, Extract, Insert)]
+/// struct User {
+///     #[column(key)]
+///     id: i32,
+///     name: String,
+/// }
+///
+/// assert_eq!(
+///     User::insert_sql("users"),
+///     r#"INSERT INTO users ("id", "name") VALUES ($1, $2) ON CONFLICT ("id") DO UPDATE SET "name" = EXCLUDED."name" RETURNING "id", "name""#,
+/// );
+///
+/// async fn upsert_user(client: &Client, user: &User) -> Result<User, Error> {
+///     let row = client
+///         .query_one(&User::insert_sql("users"), &user.params())
+///         .await?;
+///     Ok(User::extract_once(&row))
+/// }
+/// ```
+pub trait Insert {
+    /// The names of the columns, in the order in which they appear in the statement.
+    const COLUMN_NAMES: &'static [&'static str];
+
+    /// The names of the columns that identify a row.
+    ///
+    /// If this is not empty, [`Insert::insert_sql`] updates existing rows with the same
+    /// key instead of failing.
+    const KEY_COLUMNS: &'static [&'static str];
+
+    /// The parameters of the statement.
+    type Params<'a>: AsRef<[&'a (dyn ToSql + Sync)]>
+    where
+        Self: 'a;
+
+    /// Returns the parameters of the statement returned by [`Insert::insert_sql`].
+    fn params(&self) -> Self::Params<'_>;
+
+    /// Returns an `INSERT` statement for the table.
+    ///
+    /// `table` is inserted verbatim and can therefore contain a schema. The parameters
+    /// `$1, $2, ...` correspond to the columns in [`Insert::COLUMN_NAMES`].
+    ///
+    /// If [`Insert::KEY_COLUMNS`] is not empty, the statement contains an
+    /// `ON CONFLICT (...) DO UPDATE` clause that overwrites the remaining columns of
+    /// existing rows.
+    ///
+    /// The statement returns the inserted or updated row with the columns in
+    /// [`Insert::COLUMN_NAMES`]. Use [`Insert::insert_sql_returning`] to return other
+    /// columns.
+    fn insert_sql(table: &str) -> String {
+        let columns: Vec<_> = Self::COLUMN_NAMES.iter().map(|c| quote_ident(c)).collect();
+        Self::insert_sql_returning(table, &columns.join(", "))
+    }
+
+    /// Returns an `INSERT` statement for the table that returns the expressions in
+    /// `returning`.
+    ///
+    /// `returning` is inserted verbatim. It is usually `*` or a list of columns such as
+    /// columns with default values that are not fields of the type. The returned row can
+    /// then be extracted with a different type.
+    ///
+    /// Otherwise the statement is the same as the one returned by [`Insert::insert_sql`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_postgres::{Client, Error};
+    /// # use tokio_postgres_extractor::{Columns, Extract, Insert};
+    /// #[derive(Insert)]
+    /// struct NewUser<'a> {
+    ///     name: &'a str,
+    /// }
+    ///
+    /// #[derive(Columns, Extract)]
+    /// struct User {
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     NewUser::insert_sql_returning("users", "*"),
+    ///     r#"INSERT INTO users ("name") VALUES ($1) RETURNING *"#,
+    /// );
+    ///
+    /// async fn create_user(client: &Client, user: &NewUser<'_>) -> Result<User, Error> {
+    ///     let row = client
+    ///         .query_one(&NewUser::insert_sql_returning("users", "*"), &user.params())
+    ///         .await?;
+    ///     Ok(User::extract_once(&row))
+    /// }
+    /// ```
+    fn insert_sql_returning(table: &str, returning: &str) -> String {
+        let columns: Vec<_> = Self::COLUMN_NAMES.iter().map(|c| quote_ident(c)).collect();
+        let columns = columns.join(", ");
+        let params: Vec<_> = (1..=Self::COLUMN_NAMES.len())
+            .map(|i| format!("${}", i))
+            .collect();
+        let mut res = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            columns,
+            params.join(", "),
+        );
+        if !Self::KEY_COLUMNS.is_empty() {
+            let keys: Vec<_> = Self::KEY_COLUMNS.iter().map(|c| quote_ident(c)).collect();
+            let mut updated: Vec<_> = Self::COLUMN_NAMES
+                .iter()
+                .filter(|c| !Self::KEY_COLUMNS.contains(c))
+                .collect();
+            if updated.is_empty() {
+                // DO NOTHING would not return the existing row.
+                updated = Self::KEY_COLUMNS.iter().collect();
+            }
+            let updated: Vec<_> = updated
+                .into_iter()
+                .map(|c| format!("{0} = EXCLUDED.{0}", quote_ident(c)))
+                .collect();
+            res.push_str(&format!(
+                " ON CONFLICT ({}) DO UPDATE SET {}",
+                keys.join(", "),
+                updated.join(", "),
+            ));
+        }
+        res.push_str(" RETURNING ");
+        res.push_str(returning);
+        res
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{tests::connect, Columns, Extract, Insert};
+
+#[tokio::test]
+async fn insert() {
+    #[derive(Debug, PartialEq, Columns, Extract, Insert)]
+    struct Item {
+        #[column(key)]
+        id: i32,
+        #[column(name = "Name")]
+        name: Option<String>,
+        #[column(pattern = "q{}", range = 1..=2)]
+        q: [i64; 2],
+        #[column(skip)]
+        skipped: bool,
+    }
+
+    let client = connect().await;
+    client
+        .batch_execute(
+            r#"create temporary table items (id int4 primary key, "Name" text, q1 int8, q2 int8)"#,
+        )
+        .await
+        .unwrap();
+    let sql = Item::insert_sql("items");
+    let mut item = Item {
+        id: 1,
+        name: Some("a".to_string()),
+        q: [2, 3],
+        skipped: false,
+    };
+    let row = client.query_one(&sql, &item.params()).await.unwrap();
+    assert_eq!(Item::extract_once(&row), item);
+    item.name = None;
+    item.q = [4, 5];
+    let row = client.query_one(&sql, &item.params()).await.unwrap();
+    assert_eq!(Item::extract_once(&row), item);
+    let rows = client.query("select * from items", &[]).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(Item::extract_once(&rows[0]), item);
+}
+
+#[tokio::test]
+async fn returning() {
+    #[derive(Insert)]
+    struct NewItem<'a> {
+        #[column(key)]
+        name: &'a str,
+        #[column(skip)]
+        #[allow(dead_code)]
+        id: i32,
+    }
+
+    #[derive(Debug, PartialEq, Columns, Extract)]
+    struct Item {
+        id: i32,
+        name: String,
+    }
+
+    let client = connect().await;
+    client
+        .batch_execute("create temporary table serial_items (id serial, name text unique)")
+        .await
+        .unwrap();
+    let sql = NewItem::insert_sql_returning("serial_items", "*");
+    assert_eq!(
+        sql,
+        r#"INSERT INTO serial_items ("name") VALUES ($1) ON CONFLICT ("name") DO UPDATE SET "name" = EXCLUDED."name" RETURNING *"#
+    );
+    for (name, id) in [("a", 1), ("b", 2), ("a", 1)] {
+        let item = NewItem { name, id: 0 };
+        let row = client.query_one(&sql, &item.params()).await.unwrap();
+        assert_eq!(
+            Item::extract_once(&row),
+            Item {
+                id,
+                name: name.to_string(),
+            }
+        );
+    }
+}
+
+#[test]
+fn insert_sql() {
+    #[derive(Insert)]
+    #[allow(dead_code)]
+    struct Plain {
+        a: i32,
+        b: i32,
+    }
+
+    assert_eq!(
+        Plain::insert_sql("s.t"),
+        r#"INSERT INTO s.t ("a", "b") VALUES ($1, $2) RETURNING "a", "b""#
+    );
+}
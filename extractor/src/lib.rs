@@ -97,6 +97,76 @@
 
 extern crate self as tokio_postgres_extractor;
 
+/// Rewrites a statement with named parameters at compile time.
+///
+/// The first argument is a type implementing [`Params`](trait@Params) and the second
+/// argument is the statement. The macro evaluates to a
+/// [`NamedQuery<'static, T>`](params::NamedQuery). Compilation fails if a name is not a
+/// parameter of the type:
+///
+/// ```compile_fail
+/// # use tokio_postgres_extractor::{named_query, Params};
+/// #[derive(Params)]
+/// struct UserQuery {
+///     id: i32,
+/// }
+///
+/// let query = named_query!(UserQuery, "select * from users where id = :user_id");
+/// ```
+///
+/// The check uses constant evaluation and therefore the type cannot depend on generic
+/// parameters of the enclosing function.
+///
+/// # Examples
+///
+/// See the documentation of the [`params`] module.
+pub use tokio_postgres_extractor_macros::named_query;
+
+/// Proc macro for deriving the [`Changeset`](trait@Changeset) trait.
+///
+/// Every field must either be annotated with `key` or `primary_key` or have type
+/// `Option<T>`. Key fields identify the row in the `WHERE` clause of the statement. Fields that are `Some` are
+/// written to their column. Fields that are `None` are left unchanged. To set a nullable
+/// column to `NULL`, use a field of type `Option<Option<T>>`.
+///
+/// Column names are determined in the same way as by the [`Columns`](macro@Columns) proc
+/// macro, including `#[columns(rename_all = "...")]`. Of the field attributes, only `name`,
+/// `key`, `primary_key`, `skip`, and `default` are supported. Compilation fails if a field uses another
+/// attribute:
+///
+/// ```compile_fail
+/// # use tokio_postgres_extractor::Changeset;
+/// #[derive(Changeset)]
+/// struct UserChanges {
+///     #[column(key)]
+///     id: i32,
+///     #[column(alias = "display_name")]
+///     name: Option<String>,
+/// }
+/// ```
+///
+/// # Diffs
+///
+/// The `diff` attribute implements [`Diff`] for another type, usually a type deriving
+/// [`Extract`](macro@Extract):
+///
+/// ```rust,ignore
+/// #[derive(Changeset)]
+/// #[changeset(diff = User)]
+/// struct UserChanges {
+///     #[column(key)]
+///     id: i32,
+///     name: Option<String>,
+/// }
+/// ```
+///
+/// The other type must have fields with the same names. Key fields are cloned from the old
+/// value. Other fields are `Some` if their values differ and are cloned from the new value.
+///
+/// # Examples
+///
+/// See the documentation of the [`Changeset`](trait@Changeset) trait.
+pub use tokio_postgres_extractor_macros::Changeset;
 /// Proc macro for deriving the [`Columns`] trait.
 ///
 /// # Custom column names
@@ -110,6 +180,63 @@ extern crate self as tokio_postgres_extractor;
 ///
 /// to explicitly specify a name. The name must be a string literal.
 ///
+/// # Naming conventions
+///
+/// If the columns follow a different naming convention than the fields, annotate the
+/// struct with
+///
+/// ```rust,ignore
+/// #[columns(rename_all = "camelCase")]
+/// ```
+///
+/// to derive the column names from the names of the fields. The supported conventions are
+/// `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`, `snake_case`,
+/// `SCREAMING_SNAKE_CASE`, `kebab-case`, and `SCREAMING-KEBAB-CASE`. Fields with a `name`
+/// attribute, aliases, and prefixes are not renamed.
+///
+/// The other derives that use column names, such as [`Insert`](macro@Insert) and
+/// [`Changeset`](macro@Changeset), honor this attribute as well.
+///
+/// # Alternative names
+///
+/// A field can accept several column names:
+///
+/// ```rust,ignore
+/// #[column(name = "display_name", alias = "name")]
+/// display_name: String,
+/// ```
+///
+/// `alias` can be specified multiple times. If the row contains more than one of the
+/// names, the field uses the column of the first name in the order `name`, followed by
+/// the aliases in the order in which they are listed. If the field has no `name`
+/// attribute, the name of the field has the highest priority.
+///
+/// # Case-insensitive names
+///
+/// By default, column names are compared exactly. Annotating the struct with
+///
+/// ```rust,ignore
+/// #[columns(case_insensitive)]
+/// ```
+///
+/// causes column names to be compared after folding ASCII letters to lower case. This
+/// applies to all names, aliases, and prefixes of the struct. The matching strategy
+/// described below is preserved, no column name is copied or lowercased in full.
+///
+/// Names that differ but are equal after folding are rejected, as are aliases that are
+/// equal to the column name of a field:
+///
+/// ```compile_fail
+/// # use tokio_postgres_extractor::Columns;
+/// #[derive(Columns)]
+/// #[columns(case_insensitive)]
+/// struct User {
+///     #[column(name = "Id")]
+///     user_id: i32,
+///     id: i32,
+/// }
+/// ```
+///
 /// # Explicit indices
 ///
 /// If you already know the index a field maps to, you can use
@@ -121,6 +248,142 @@ extern crate self as tokio_postgres_extractor;
 ///
 /// to specify it.
 ///
+/// # Skipped fields
+///
+/// Fields that do not map to any column can be skipped:
+///
+/// ```rust,ignore
+/// #[column(skip)]
+/// cache: Option<Vec<u8>>,
+/// #[column(skip, default = 1)]
+/// version: i32,
+/// ```
+///
+/// Skipped fields are not part of the associated `Columns` type. When deriving
+/// [`Extract`](macro@Extract), skipped fields are initialized with [`Default::default`] or
+/// with the expression specified with `default`. Fields of type `PhantomData` are skipped
+/// automatically and, like skipped fields, only accept `default`:
+///
+/// ```compile_fail
+/// # use std::marker::PhantomData;
+/// # use tokio_postgres_extractor::Columns;
+/// #[derive(Columns)]
+/// struct User<T> {
+///     id: i32,
+///     #[column(name = "x")]
+///     marker: PhantomData<T>,
+/// }
+/// ```
+///
+/// # Multi-column fields
+///
+/// A field can be constructed from multiple columns:
+///
+/// ```rust,ignore
+/// #[column(from_columns("amount", "currency"), with = Money::new)]
+/// price: Money,
+/// ```
+///
+/// All of the columns are resolved together with the columns of the other fields. When
+/// deriving [`Extract`](macro@Extract), the function specified with `with` is called with
+/// the decoded columns in the order in which they are listed.
+///
+/// # Column groups
+///
+/// A field of array type can be constructed from a group of columns whose names follow a
+/// pattern:
+///
+/// ```rust,ignore
+/// #[column(pattern = "q{}_revenue", range = 1..=4)]
+/// revenue: [i64; 4],
+/// ```
+///
+/// The placeholder `{}` is replaced by each number in the range. The resulting names are
+/// resolved as if they had been specified individually.
+///
+/// If the number of columns is only known at runtime, a field of type `Vec<T>` can
+/// collect all columns whose name consists of a prefix followed by a number:
+///
+/// ```rust,ignore
+/// #[column(prefix_vec = "tag_")]
+/// tags: Vec<String>,
+/// ```
+///
+/// The columns are ordered by their number. Columns that are matched by name by other
+/// fields are not collected. If a type contains such a field, the associated `Columns`
+/// type is [`DynamicColumns`] and the mapping always inspects all columns.
+///
+/// # Unmapped columns
+///
+/// A single field can collect all columns that are not matched by name by other fields:
+///
+/// ```rust,ignore
+/// #[column(rest)]
+/// extra: HashMap<String, DynValue>,
+/// ```
+///
+/// The field must have type `HashMap<String, T>`, `BTreeMap<String, T>`, or
+/// `Vec<(String, T)>` where `T` implements [`FromSql`](tokio_postgres::types::FromSql).
+/// The keys are the names of the columns. [`DynValue`] can be used to decode columns of
+/// any type. The unmapped columns are computed together with the mapping of the other
+/// fields.
+///
+/// # Newtypes
+///
+/// A struct with a single field can be declared transparent:
+///
+/// ```rust,ignore
+/// #[derive(Debug, Columns, Extract)]
+/// #[columns(transparent, from_sql, to_sql)]
+/// struct UserId(i64);
+/// ```
+///
+/// The field is read from the first column of the row, whatever its name. Other fields
+/// must be skipped. `from_sql` and `to_sql` additionally implement
+/// [`FromSql`](tokio_postgres::types::FromSql) and
+/// [`ToSql`](tokio_postgres::types::ToSql) by delegating to the type of the field. This
+/// allows the type to be used as the type of a field of another struct or as a query
+/// parameter. `ToSql` requires the type to implement `Debug`.
+///
+/// # Positional mapping
+///
+/// If the order of the columns is known, the mapping by name can be replaced by a mapping
+/// by position:
+///
+/// ```rust,ignore
+/// #[derive(Columns)]
+/// #[columns(by_position)]
+/// struct User {
+///     id: i64,
+///     name: String,
+/// }
+/// ```
+///
+/// The fields then map to the columns of the row in order. In debug builds, the mapping
+/// panics if the row has a different number of columns or if the name of a
+/// column does not match the name of the field. `#[column(name = "...")]` can be used to
+/// specify the expected name. Use `#[columns(by_position(checked))]` to perform these
+/// checks in release builds as well.
+///
+/// # Remote types
+///
+/// Types defined in other crates can be extracted by deriving the traits for a mirror
+/// definition:
+///
+/// ```rust,ignore
+/// #[derive(Columns, Extract)]
+/// #[columns(remote = "domain::User")]
+/// struct UserDef {
+///     id: i64,
+///     name: String,
+/// }
+/// ```
+///
+/// The mirror must have the same fields as the remote type and the fields must be
+/// visible. Instead of [`Extract`](trait@Extract), the [`Extract`](macro@Extract) proc
+/// macro implements [`ExtractAs<domain::User>`](ExtractAs) for the mirror. The remote type
+/// can then be extracted through the wrapper [`Remote<UserDef, domain::User>`](Remote).
+///
 /// # Implementation
 ///
 /// The critical section in the expansion of
@@ -183,19 +446,600 @@ extern crate self as tokio_postgres_extractor;
 /// above is not possible and the implementation will have to perform multiple string
 /// comparisons. Even this is still much faster than using the phf crate or similar.
 pub use tokio_postgres_extractor_macros::Columns;
+/// Proc macro for deriving the [`CopyIn`](trait@CopyIn) trait.
+///
+/// Like the [`CopyOut`](macro@CopyOut) proc macro, the derived implementation identifies
+/// columns by position and [`copy::copy_in_statement`] generates a statement that consumes
+/// them in the order in which the fields are declared. The values are encoded with
+/// [`ToSql`](tokio_postgres::types::ToSql).
+///
+/// `name`, `pattern`, `range`, `skip`, and `sql_type` behave as described in the
+/// documentation of the [`Columns`](macro@Columns) and [`CopyOut`](macro@CopyOut) proc
+/// macros. Skipped fields are not written. `via`, `with`, and `with_row` are not
+/// supported.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::CopyIn;
+/// #[derive(CopyIn)]
+/// struct User {
+///     id: i32,
+///     #[column(name = "user_name")]
+///     name: String,
+///     #[column(skip)]
+///     cached: bool,
+/// }
+///
+/// assert_eq!(User::COLUMN_NAMES, ["id", "user_name"]);
+/// ```
+pub use tokio_postgres_extractor_macros::CopyIn;
+/// Proc macro for deriving the [`CopyOut`](trait@CopyOut) trait.
+///
+/// The binary `COPY` format does not contain column names. The columns are therefore
+/// identified by position: the derived implementation expects the columns in the order
+/// in which the fields are declared and [`copy::copy_out_statement`] generates a
+/// statement that produces them in this order. Every field must map to named columns,
+/// that is, `idx` and fields that map to a variable number of columns are not supported.
+///
+/// `name`, `pattern`, `range`, `skip`, `default`, `via`, and `null_default` behave as
+/// described in the documentation of the [`Columns`](macro@Columns) and
+/// [`Extract`](macro@Extract) proc macros. So do the `finish` and `validate` attributes.
+/// `with` and `with_row` are not supported. If a `via` conversion fails, extraction
+/// returns [`Error::Conversion`].
+///
+/// # Column types
+///
+/// The binary format requires the type of every column to be known before the first row
+/// is read. By default, the type is determined with the [`SqlType`] trait. For fields
+/// whose types do not implement `SqlType`, the type can be specified explicitly:
+///
+/// ```rust,ignore
+/// #[column(sql_type = Type::JSONB)]
+/// payload: Json<Payload>,
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::CopyOut;
+/// #[derive(CopyOut)]
+/// struct User {
+///     id: i32,
+///     #[column(name = "user_name")]
+///     name: String,
+///     #[column(null_default)]
+///     balance: i64,
+/// }
+///
+/// assert_eq!(User::COLUMN_NAMES, ["id", "user_name", "balance"]);
+/// ```
+pub use tokio_postgres_extractor_macros::CopyOut;
 /// Proc macro for deriving the [`Extract`] trait.
+///
+/// By default, every field is decoded with [`Row::get`] and must therefore implement
+/// [`FromSql`](tokio_postgres::types::FromSql).
+///
+/// # Converting from another type
+///
+/// If the type of a field does not implement `FromSql`, you can decode the column as
+/// another type and convert it with [`TryFrom`]:
+///
+/// ```rust,ignore
+/// #[column(via = String)]
+/// role: Role,
+/// ```
+///
+/// This requires `Role: TryFrom<String>` and that the error type implements
+/// [`Display`](std::fmt::Display). Since every `From` implementation also provides a
+/// `TryFrom` implementation, this works for infallible conversions as well. If the
+/// conversion fails, the panicking functions such as [`Extract::extract_once`] panic and
+/// the fallible functions such as [`Extract::try_extract_once`] return
+/// [`Error::Conversion`].
+///
+/// # NULL values
+///
+/// If a column can contain `NULL` but the field should not be an `Option`, you can replace
+/// `NULL` by the default value of the type:
+///
+/// ```rust,ignore
+/// #[column(null_default)]
+/// balance: i64,
+/// ```
+///
+/// or by the value of an expression:
+///
+/// ```rust,ignore
+/// #[column(null_default = "unknown".to_string())]
+/// name: String,
+/// ```
+///
+/// The column is decoded as `Option<T>`. This can be combined with `via`, in which case
+/// the conversion is only performed for values that are not `NULL`.
+///
+/// # Custom decoding functions
+///
+/// Alternatively, you can specify a function that is called with the decoded column:
+///
+/// ```rust,ignore
+/// #[column(with = parse_tags)]
+/// tags: Vec<Tag>,
+/// ```
+///
+/// The argument of the function must implement `FromSql` and the function must return
+/// the type of the field. The argument type is inferred from the signature of the
+/// function.
+///
+/// If the field is constructed from multiple columns with `from_columns`, the function is
+/// called with one argument per column. See the documentation of the
+/// [`Columns`](macro@Columns) proc macro.
+///
+/// If the function needs access to the row, for example, to inspect the type of the
+/// column before decoding it, you can instead specify a function that is called with the
+/// row and the index of the column:
+///
+/// ```rust,ignore
+/// #[column(with_row = decode_amount)]
+/// amount: Amount,
+/// ```
+///
+/// The function must have the signature `fn(&Row, usize) -> Result<T, E>` where `T` is
+/// the type of the field and [`Error`] implements `From<E>`, for example,
+/// `tokio_postgres::Error` or [`Error`] itself. If the function returns an error, the
+/// panicking functions such as [`Extract::extract_once`] panic and the fallible functions
+/// such as [`Extract::try_extract_once`] return the converted error. `with_row` cannot be
+/// combined with `from_columns`, `via`, or `null_default`.
+///
+/// # Post-processing
+///
+/// You can specify a function that is called after all fields have been decoded. Together
+/// with skipped fields, this can be used to compute derived fields:
+///
+/// ```rust,ignore
+/// #[extract(finish = Self::finish)]
+/// struct User {
+///     first_name: String,
+///     last_name: String,
+///     #[column(skip)]
+///     full_name: String,
+/// }
+///
+/// impl User {
+///     fn finish(&mut self) {
+///         self.full_name = format!("{} {}", self.first_name, self.last_name);
+///     }
+/// }
+/// ```
+///
+/// # Validation
+///
+/// You can specify a function that validates the extracted value:
+///
+/// ```rust,ignore
+/// #[extract(validate = Self::check)]
+/// struct Period {
+///     start: i64,
+///     end: i64,
+/// }
+///
+/// impl Period {
+///     fn check(&self) -> Result<(), &'static str> {
+///         match self.start <= self.end {
+///             true => Ok(()),
+///             false => Err("start must not be after end"),
+///         }
+///     }
+/// }
+/// ```
+///
+/// The function must return `Result<(), E>` where `E` can be converted into a
+/// `Box<dyn Error + Send + Sync>`. The validation function runs after the `finish`
+/// function. If the validation fails, the panicking functions such as
+/// [`Extract::extract_once`] panic and the fallible functions such as
+/// [`Extract::try_extract_once`] return [`Error::Validation`].
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::{Columns, Extract};
+/// struct UserId(i64);
+///
+/// impl From<i64> for UserId {
+///     fn from(id: i64) -> Self {
+///         Self(id)
+///     }
+/// }
+///
+/// fn split_tags(tags: &str) -> Vec<String> {
+///     tags.split(',').map(|t| t.to_string()).collect()
+/// }
+///
+/// #[derive(Columns, Extract)]
+/// struct User {
+///     #[column(via = i64)]
+///     id: UserId,
+///     #[column(with = split_tags)]
+///     tags: Vec<String>,
+/// }
+/// ```
 pub use tokio_postgres_extractor_macros::Extract;
+/// Proc macro for deriving the [`ExtractText`](trait@ExtractText) trait.
+///
+/// The derived implementation maps fields to columns in the same way as the
+/// [`Columns`](macro@Columns) proc macro and supports the same attributes. The type must
+/// therefore also derive [`Columns`](macro@Columns).
+///
+/// Each value is parsed with [`FromStr`](std::str::FromStr). If a field has type
+/// `Option<T>`, `NULL` values produce `None`. Otherwise `NULL` values produce
+/// [`Error::Null`] unless the field is annotated with `null_default`. `via`, `finish`, and
+/// `validate` behave as described in the documentation of the
+/// [`Extract`](macro@Extract) proc macro. `with` and `with_row` are not supported.
+///
+/// # Custom parsing functions
+///
+/// A custom parsing function can be specified with
+///
+/// ```rust,ignore
+/// #[column(parse_with = parse_tags)]
+/// tags: Vec<String>,
+/// ```
+///
+/// The function has the signature `fn(&str) -> Result<T, E>` where `E` can be converted to
+/// `Box<dyn Error + Send + Sync>`. If the field has type `Option<T>`, the function is only
+/// called for values that are not `NULL`. For column groups, the function is called for
+/// each column.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres::SimpleQueryRow;
+/// # use tokio_postgres_extractor::{Columns, Error, ExtractText};
+/// #[derive(Columns, ExtractText)]
+/// struct User {
+///     id: i32,
+///     name: Option<String>,
+/// }
+///
+/// fn map_user(row: &SimpleQueryRow) -> Result<User, Error> {
+///     User::extract_text_once(row)
+/// }
+/// ```
+pub use tokio_postgres_extractor_macros::ExtractText;
+/// Proc macro for deriving the [`Insert`](trait@Insert) trait.
+///
+/// Each field is written to the column that the [`Columns`](macro@Columns) proc macro would
+/// map it to and is encoded with [`ToSql`](tokio_postgres::types::ToSql). `name`,
+/// `pattern`, `range`, and `skip` behave as described in the documentation of the
+/// [`Columns`](macro@Columns) proc macro. Skipped fields are not written, which allows
+/// columns with default values such as generated IDs to be filled by the database. `idx`,
+/// `via`, `with`, `with_row`, and fields that map to a variable number of columns are not
+/// supported.
+///
+/// # Upserts
+///
+/// Fields annotated with `key` form the conflict target of the statement:
+///
+/// ```rust,ignore
+/// #[column(key)]
+/// id: i32,
+/// ```
+///
+/// If a row with the same key already exists, the remaining columns of the row are
+/// overwritten.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::Insert;
+/// #[derive(Insert)]
+/// struct Tag {
+///     #[column(key)]
+///     post_id: i32,
+///     #[column(key)]
+///     name: String,
+///     #[column(skip)]
+///     id: i64,
+/// }
+///
+/// assert_eq!(
+///     Tag::insert_sql("tags"),
+///     r#"INSERT INTO tags ("post_id", "name") VALUES ($1, $2) ON CONFLICT ("post_id", "name") DO UPDATE SET "post_id" = EXCLUDED."post_id", "name" = EXCLUDED."name" RETURNING "post_id", "name""#,
+/// );
+/// ```
+pub use tokio_postgres_extractor_macros::Insert;
+/// Proc macro for deriving the [`Paginate`](trait@Paginate) trait.
+///
+/// The fields annotated with `#[column(sort_key)]` define the ordering of the pages. They
+/// are compared in the order in which they are declared:
+///
+/// ```rust,ignore
+/// #[derive(Columns, Extract, Paginate)]
+/// struct Post {
+///     #[column(sort_key)]
+///     created: i64,
+///     #[column(sort_key)]
+///     id: i32,
+///     title: String,
+/// }
+/// ```
+///
+/// Each sort key must map to a single named column and its type must implement
+/// [`ToSql`](tokio_postgres::types::ToSql). The type of the column is determined with
+/// [`SqlType`] unless it is specified with `sql_type`, as described in the documentation
+/// of the [`CopyOut`](macro@CopyOut) proc macro.
+///
+/// The type must also implement [`Extract`](trait@Extract) for all lifetimes.
+///
+/// # Examples
+///
+/// See the documentation of the [`page`] module.
+pub use tokio_postgres_extractor_macros::Paginate;
+/// Proc macro for deriving the [`Params`](trait@Params) trait.
+///
+/// Each field is a parameter whose name is determined in the same way as the name of a
+/// column by the [`Columns`](macro@Columns) proc macro:
+///
+/// ```rust,ignore
+/// #[derive(Params)]
+/// struct UserQuery<'a> {
+///     id: i32,
+///     #[column(name = "org")]
+///     organization: &'a str,
+///     #[column(skip)]
+///     cached: bool,
+/// }
+/// ```
+///
+/// The types of the fields must implement [`ToSql`](tokio_postgres::types::ToSql).
+/// `name`, `pattern`, `range`, and `skip` are supported.
+///
+/// # Examples
+///
+/// See the documentation of the [`params`] module.
+pub use tokio_postgres_extractor_macros::Params;
+/// Proc macro for deriving the [`Table`](trait@Table) trait.
+///
+/// The name of the table is specified with the `table` attribute and the primary key with
+/// the `primary_key` attribute:
+///
+/// ```rust,ignore
+/// #[derive(Columns, Extract, Table)]
+/// #[table(name = "users")]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i32,
+///     name: String,
+/// }
+/// ```
+///
+/// The primary key must be a single field that maps to a single named column. Its type
+/// must implement [`ToSql`](tokio_postgres::types::ToSql). The name of the column is
+/// determined in the same way as by the [`Columns`](macro@Columns) proc macro.
+///
+/// The type must also implement [`Extract`](trait@Extract) for all lifetimes. The rows are
+/// loaded with `SELECT *` and extracted with the mapping of the [`Columns`] implementation.
+///
+/// # Examples
+///
+/// See the documentation of the [`table`] module.
+pub use tokio_postgres_extractor_macros::Table;
+/// Proc macro for deriving the [`Unnest`](trait@Unnest) trait.
+///
+/// Each column is passed as an array of the values of the column in all rows. The columns
+/// are determined in the same way as by the [`CopyIn`](macro@CopyIn) proc macro: `name`,
+/// `pattern`, `range`, `skip`, and `sql_type` are supported. `via`, `with`, and
+/// `with_row` are not supported.
+///
+/// The types of the fields must implement [`SqlArrayType`] unless the type of the
+/// elements is specified with `sql_type`. Fields that are arrays themselves are therefore
+/// rejected at compile time:
+///
+/// ```compile_fail
+/// # use tokio_postgres_extractor::Unnest;
+/// #[derive(Unnest)]
+/// struct Post {
+///     id: i32,
+///     tags: Vec<String>,
+/// }
+/// ```
+///
+/// # Examples
+///
+/// See the documentation of the [`unnest`] module.
+pub use tokio_postgres_extractor_macros::Unnest;
 use {crate::sealed::Sealed, std::ops::Index, tokio_postgres::Row};
+pub use {
+    changeset::{diff, Changeset, Diff},
+    copy::{CopyIn, CopyOut},
+    dyn_value::DynValue,
+    error::Error,
+    insert::Insert,
+    page::Paginate,
+    params::Params,
+    remote::{ExtractAs, Remote},
+    sql_type::{SqlArrayType, SqlType},
+    table::Table,
+    text::ExtractText,
+    unnest::Unnest,
+};
 
+mod changeset;
+pub mod copy;
+pub mod csv;
+mod dyn_value;
+mod error;
+mod insert;
 pub mod iter;
+pub mod notification;
+pub mod page;
+pub mod params;
+pub mod portal;
+mod remote;
+pub mod replication;
+mod sql_type;
 pub mod stream;
+pub mod table;
+pub mod text;
+pub mod unnest;
 
 #[cfg(test)]
 mod tests;
 
 #[doc(hidden)]
 pub mod private {
-    pub use tokio_postgres;
+    use {
+        crate::{params::NamedQuery, text::TextRow, Error},
+        std::{error::Error as StdError, fmt::Display},
+    };
+
+    pub use {bytes, tokio_postgres};
+
+    /// Adapts the columns of a [`TextRow`] to the interface used by the generated
+    /// mapping code.
+    pub struct TextColumns<'a> {
+        columns: Vec<TextColumn<'a>>,
+    }
+
+    impl<'a> TextColumns<'a> {
+        pub fn new(row: &'a impl TextRow) -> Self {
+            let columns = (0..row.num_columns())
+                .map(|idx| TextColumn(row.column_name(idx)))
+                .collect();
+            Self { columns }
+        }
+
+        pub fn columns(&self) -> &[TextColumn<'a>] {
+            &self.columns
+        }
+    }
+
+    pub struct TextColumn<'a>(&'a str);
+
+    impl<'a> TextColumn<'a> {
+        pub fn name(&self) -> &'a str {
+            self.0
+        }
+    }
+
+    pub fn convert<T, U>(idx: usize, value: U) -> T
+    where
+        T: TryFrom<U>,
+        T::Error: Display,
+    {
+        match T::try_from(value) {
+            Ok(v) => v,
+            Err(e) => panic!("error converting column {}: {}", idx, e),
+        }
+    }
+
+    pub fn try_convert<T, U>(idx: usize, value: U) -> Result<T, Error>
+    where
+        T: TryFrom<U>,
+        T::Error: Display,
+    {
+        T::try_from(value).map_err(|e| Error::Conversion {
+            column: idx,
+            error: e.to_string().into(),
+        })
+    }
+
+    pub fn with_row<T, E>(value: Result<T, E>) -> T
+    where
+        Error: From<E>,
+    {
+        match value {
+            Ok(v) => v,
+            Err(e) => panic!("{}", Error::from(e)),
+        }
+    }
+
+    pub fn parse_text<T, E>(
+        column: usize,
+        value: Option<&str>,
+        parse: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Result<Option<T>, Error>
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        value
+            .map(|v| {
+                parse(v).map_err(|e| Error::Conversion {
+                    column,
+                    error: e.into(),
+                })
+            })
+            .transpose()
+    }
+
+    pub fn text_non_null<T>(column: usize, value: Option<T>) -> Result<T, Error> {
+        value.ok_or(Error::Null { column })
+    }
+
+    pub fn numbered_column(name: &str, prefix: &str) -> Option<usize> {
+        let n = name.strip_prefix(prefix)?;
+        if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        n.parse().ok()
+    }
+
+    pub fn numbered_column_ignore_ascii_case(name: &str, prefix: &str) -> Option<usize> {
+        match name.get(..prefix.len()) {
+            Some(p) if p.eq_ignore_ascii_case(prefix) => numbered_column(name, p),
+            _ => None,
+        }
+    }
+
+    pub fn sort_numbered(mut columns: Vec<(usize, usize)>) -> Vec<usize> {
+        columns.sort_unstable();
+        columns.into_iter().map(|(_, idx)| idx).collect()
+    }
+
+    pub fn validate<E>(ty: &'static str, res: Result<(), E>)
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        if let Err(e) = res {
+            panic!("validation of `{}` failed: {}", ty, e.into());
+        }
+    }
+
+    pub fn try_validate<E>(ty: &'static str, res: Result<(), E>) -> Result<(), Error>
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        res.map_err(|e| Error::Validation {
+            ty,
+            error: e.into(),
+        })
+    }
+
+    /// Returns the index of `name` in `names` or `usize::MAX` if it is not contained.
+    pub const fn param_index(names: &[&str], name: &str) -> usize {
+        let mut idx = 0;
+        while idx < names.len() {
+            let n = names[idx].as_bytes();
+            let name = name.as_bytes();
+            if n.len() == name.len() {
+                let mut i = 0;
+                while i < n.len() && n[i] == name[i] {
+                    i += 1;
+                }
+                if i == n.len() {
+                    return idx;
+                }
+            }
+            idx += 1;
+        }
+        usize::MAX
+    }
+
+    pub const fn named_query<P>(
+        sql: &'static str,
+        indices: &'static [usize],
+    ) -> NamedQuery<'static, P> {
+        NamedQuery::from_static(sql, indices)
+    }
 }
 
 /// A type whose fields map to Postgres columns.
@@ -212,7 +1056,7 @@ pub mod private {
 /// ```
 ///
 /// In this case the associated `Columns` type is `[usize; N]` where `N` is the number of
-/// fields.
+/// columns used by the fields. Usually this is the number of fields.
 ///
 /// Assume that a [`Row`] was created from the following query:
 ///
@@ -226,18 +1070,49 @@ pub mod private {
 pub trait Columns {
     /// The type identifying the columns.
     ///
-    /// This should always be `[usize; N]` where `N` is the number of fields. In a future
-    /// version of this crate, this field will be replaced by
-    ///
-    /// ```
-    /// pub trait Columns {
-    ///     const NUM_COLUMNS: usize;
-    /// }
-    /// ```
+    /// If every field maps to a fixed number of columns, this is `[usize; N]` where `N` is
+    /// the number of these columns. If some fields map to a number of columns that is only
+    /// known at runtime, for example, fields annotated with `prefix_vec` or `rest`, this is
+    /// [`DynamicColumns<N, M>`](DynamicColumns) where `M` is the number of such fields.
     type Columns: Unpin + Index<usize, Output = usize>;
 
     /// Returns the mapping from the type's fields to the columns in a [`Row`].
-    fn columns(row: &Row) -> Self::Columns;
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Columns::try_columns`] returns an error.
+    fn columns(row: &Row) -> Self::Columns {
+        match Self::try_columns(row) {
+            Ok(columns) => columns,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Returns the mapping from the type's fields to the columns in a [`Row`].
+    ///
+    /// Returns [`Error::Mapping`] if a field cannot be mapped to a column.
+    fn try_columns(row: &Row) -> Result<Self::Columns, Error>;
+}
+
+/// The mapping from fields to columns of a type with a variable number of columns.
+///
+/// The [`Columns`](macro@Columns) proc macro uses this type instead of `[usize; N]` if
+/// some fields map to a number of columns that is only known at runtime. For example,
+/// fields annotated with `#[column(prefix_vec = "...")]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynamicColumns<const N: usize, const M: usize> {
+    /// The columns of the fields that map to a fixed number of columns.
+    pub fixed: [usize; N],
+    /// The columns of the fields that map to a variable number of columns.
+    pub dynamic: [Vec<usize>; M],
+}
+
+impl<const N: usize, const M: usize> Index<usize> for DynamicColumns<N, M> {
+    type Output = usize;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.fixed[index]
+    }
 }
 
 /// A type that can be extracted from a [`Row`].
@@ -400,6 +1275,61 @@ pub trait Extract<'row>: Columns + Sized {
     ///
     /// Panics if [`Row::get`] panics.
     fn extract_with_columns(columns: &<Self as Columns>::Columns, row: &'row Row) -> Self;
+
+    /// Extracts an instance of the type from a [`Row`], returning an error instead of
+    /// panicking if a field cannot be mapped to a column or a column cannot be decoded.
+    ///
+    /// This is the fallible version of [`Extract::extract_once`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_postgres::Row;
+    /// # use tokio_postgres_extractor::{Columns, Error, Extract};
+    /// #[derive(Columns, Extract)]
+    /// struct User<'a> {
+    ///     id: i32,
+    ///     name: &'a str,
+    /// }
+    ///
+    /// fn map_user(row: &Row) -> Result<User<'_>, Error> {
+    ///     User::try_extract_once(row)
+    /// }
+    /// ```
+    fn try_extract_once(row: &'row Row) -> Result<Self, Error> {
+        Self::try_extract(&mut None, row)
+    }
+
+    /// Extracts an instance of the type from a [`Row`], memorizing the mapping between
+    /// fields and columns and returning an error instead of panicking if a field cannot be
+    /// mapped to a column or a column cannot be decoded.
+    ///
+    /// This is the fallible version of [`Extract::extract`]. The mapping is computed with
+    /// [`Columns::try_columns`].
+    fn try_extract(
+        columns: &mut Option<<Self as Columns>::Columns>,
+        row: &'row Row,
+    ) -> Result<Self, Error> {
+        let columns = match columns {
+            Some(columns) => columns,
+            None => columns.insert(<Self as Columns>::try_columns(row)?),
+        };
+        Self::try_extract_with_columns(columns, row)
+    }
+
+    /// Extracts an instance of the type from a [`Row`] and a mapping between the
+    /// fields and columns, returning an error instead of panicking if a column cannot be
+    /// decoded.
+    ///
+    /// This is the fallible version of [`Extract::extract_with_columns`]. The derived
+    /// implementation uses [`Row::try_get`]. The default implementation calls
+    /// [`Extract::extract_with_columns`] and never returns an error.
+    fn try_extract_with_columns(
+        columns: &<Self as Columns>::Columns,
+        row: &'row Row,
+    ) -> Result<Self, Error> {
+        Ok(Self::extract_with_columns(columns, row))
+    }
 }
 
 /// A type that can be extracted from a [`Row`] without borrowing the [`Row`].
@@ -492,6 +1422,38 @@ pub trait RowExtractExt: Sealed {
     fn extract_with_columns<'row, T>(&'row self, columns: &<T as Columns>::Columns) -> T
     where
         T: Extract<'row>;
+
+    /// Extracts an instance of `T` from this [`Row`], returning an error instead of
+    /// panicking if a field cannot be mapped to a column or a column cannot be decoded.
+    ///
+    /// This is equivalent to [`T::try_extract_once(self)`][Extract::try_extract_once].
+    fn try_extract_once<'row, T>(&'row self) -> Result<T, Error>
+    where
+        T: Extract<'row>;
+
+    /// Extracts an instance of `T` from this [`Row`], memorizing the mapping between
+    /// fields and columns and returning an error instead of panicking if a field cannot be
+    /// mapped to a column or a column cannot be decoded.
+    ///
+    /// This is equivalent to [`T::try_extract(columns, self)`][Extract::try_extract].
+    fn try_extract<'row, T>(
+        &'row self,
+        columns: &mut Option<<T as Columns>::Columns>,
+    ) -> Result<T, Error>
+    where
+        T: Extract<'row>;
+
+    /// Extracts an instance of `T` from this [`Row`] and a mapping between the
+    /// fields and columns, returning an error instead of panicking if a column cannot be
+    /// decoded.
+    ///
+    /// This is equivalent to [`T::try_extract_with_columns(columns, self)`][Extract::try_extract_with_columns].
+    fn try_extract_with_columns<'row, T>(
+        &'row self,
+        columns: &<T as Columns>::Columns,
+    ) -> Result<T, Error>
+    where
+        T: Extract<'row>;
 }
 
 impl Sealed for Row {}
@@ -513,6 +1475,33 @@ impl RowExtractExt for Row {
     {
         T::extract_with_columns(columns, self)
     }
+
+    fn try_extract_once<'row, T>(&'row self) -> Result<T, Error>
+    where
+        T: Extract<'row>,
+    {
+        T::try_extract_once(self)
+    }
+
+    fn try_extract<'row, T>(
+        &'row self,
+        columns: &mut Option<<T as Columns>::Columns>,
+    ) -> Result<T, Error>
+    where
+        T: Extract<'row>,
+    {
+        T::try_extract(columns, self)
+    }
+
+    fn try_extract_with_columns<'row, T>(
+        &'row self,
+        columns: &<T as Columns>::Columns,
+    ) -> Result<T, Error>
+    where
+        T: Extract<'row>,
+    {
+        T::try_extract_with_columns(columns, self)
+    }
 }
 
 mod sealed {
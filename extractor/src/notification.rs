@@ -0,0 +1,450 @@
+//! Extraction from notification payloads.
+//!
+//! Notifications sent with `NOTIFY` or `pg_notify` carry a text payload. If the payload is
+//! a JSON object or a list of `key=value` pairs, it can be extracted into a type
+//! implementing [`ExtractText`]. The keys are used as column names and the values are
+//! parsed with [`FromStr`](std::str::FromStr).
+//!
+//! # JSON objects
+//!
+//! String values are unescaped. Numbers and booleans are used verbatim. Nested objects
+//! and arrays are passed on as JSON text. `null` is `NULL`.
+//!
+//! ```json
+//! {"id": 1, "name": "alice", "tags": ["a", "b"], "deleted": null}
+//! ```
+//!
+//! # `key=value` pairs
+//!
+//! Pairs are separated by whitespace. Values can be quoted with single quotes, in which
+//! case `\` escapes the next character. As with [CSV inputs](crate::csv), unquoted empty
+//! values are `NULL` and quoted empty values are empty strings.
+//!
+//! ```text
+//! id=1 name='alice smith' deleted=
+//! ```
+//!
+//! Payloads starting with `{` are interpreted as JSON objects.
+//!
+//! # Examples
+//!
+//! ```
+//! # use futures_util::{stream, TryStreamExt};
+//! # use tokio_postgres::{Client, Connection, Error, Socket};
+//! # use tokio_postgres::tls::NoTlsStream;
+//! # use tokio_postgres_extractor::{Columns, ExtractText};
+//! # use tokio_postgres_extractor::notification::NotificationExtractExt;
+//! #[derive(Columns, ExtractText)]
+//! struct UserChanged {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! async fn watch_users(
+//!     client: &Client,
+//!     mut connection: Connection<Socket, NoTlsStream>,
+//! ) -> Result<(), tokio_postgres_extractor::Error> {
+//!     client.batch_execute("listen users").await?;
+//!     let mut changes = stream::poll_fn(move |cx| connection.poll_message(cx))
+//!         .extract_notifications::<UserChanged>("users");
+//!     while let Some(change) = changes.try_next().await? {
+//!         println!("user {} is now called {}", change.id, change.name);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use {
+    crate::{
+        text::{sealed::Sealed, TextRow},
+        Error, ExtractText,
+    },
+    futures_core::Stream,
+    pin_project::pin_project,
+    std::{
+        marker::PhantomData,
+        pin::Pin,
+        str::Chars,
+        task::{ready, Context, Poll},
+    },
+    tokio_postgres::AsyncMessage,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Extracts a `T` from the payload of a notification.
+///
+/// Returns [`Error::Mapping`] if the payload does not contain a key for every field.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::{Columns, ExtractText};
+/// # use tokio_postgres_extractor::notification::extract_payload;
+/// #[derive(Debug, PartialEq, Columns, ExtractText)]
+/// struct Event {
+///     id: i32,
+///     kind: Option<String>,
+/// }
+///
+/// let event: Event = extract_payload(r#"{"kind": "insert", "id": 1}"#).unwrap();
+/// assert_eq!(event, Event { id: 1, kind: Some("insert".to_string()) });
+///
+/// let event: Event = extract_payload("id=2 kind=").unwrap();
+/// assert_eq!(event, Event { id: 2, kind: None });
+/// ```
+pub fn extract_payload<T: ExtractText>(payload: &str) -> Result<T, Error> {
+    let payload = Payload::parse(payload).map_err(|message| Error::Payload { message })?;
+    let columns = T::try_text_columns(&payload)?;
+    T::extract_text_with_columns(&columns, &payload)
+}
+
+/// A [`Stream`] producing `T`s from the notifications of a channel.
+///
+/// Construct it using [`NotificationExtractExt::extract_notifications`].
+///
+/// Messages that are not notifications and notifications on other channels are skipped.
+/// If a payload cannot be extracted, the stream produces an error and continues with the
+/// next notification.
+#[pin_project]
+pub struct NotificationExtractStream<T, S> {
+    /// The underlying stream.
+    ///
+    /// This field is public for easier access.
+    #[pin]
+    pub stream: S,
+    channel: String,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, S, E> Stream for NotificationExtractStream<T, S>
+where
+    T: ExtractText,
+    S: Stream<Item = Result<AsyncMessage, E>>,
+    E: Into<Error>,
+{
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut slf = self.project();
+        loop {
+            let notification = match ready!(slf.stream.as_mut().poll_next(cx)) {
+                Some(Ok(AsyncMessage::Notification(n))) => n,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+                None => return Poll::Ready(None),
+            };
+            if notification.channel() == slf.channel.as_str() {
+                return Poll::Ready(Some(extract_payload(notification.payload())));
+            }
+        }
+    }
+}
+
+/// Extension trait for extracting from a stream of [`AsyncMessage`]s.
+///
+/// Such a stream can be created from
+/// [`Connection::poll_message`](tokio_postgres::Connection::poll_message).
+pub trait NotificationExtractExt: Sized {
+    /// Turns the stream into a [`Stream`] over the payloads of the notifications on the
+    /// channel.
+    ///
+    /// See the [module documentation](self) for the supported payload formats.
+    fn extract_notifications<T: ExtractText>(
+        self,
+        channel: &str,
+    ) -> NotificationExtractStream<T, Self>;
+}
+
+impl<S, E> NotificationExtractExt for S
+where
+    S: Stream<Item = Result<AsyncMessage, E>>,
+    E: Into<Error>,
+{
+    fn extract_notifications<T: ExtractText>(
+        self,
+        channel: &str,
+    ) -> NotificationExtractStream<T, Self> {
+        NotificationExtractStream {
+            stream: self,
+            channel: channel.to_string(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// A parsed payload.
+#[derive(Debug, Default, PartialEq)]
+struct Payload {
+    keys: Vec<String>,
+    values: Vec<Option<String>>,
+}
+
+impl Sealed for Payload {}
+
+impl TextRow for Payload {
+    fn num_columns(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn column_name(&self, idx: usize) -> &str {
+        &self.keys[idx]
+    }
+
+    fn get(&self, idx: usize) -> Option<&str> {
+        self.values[idx].as_deref()
+    }
+}
+
+impl Payload {
+    fn parse(payload: &str) -> Result<Self, String> {
+        let trimmed = payload.trim_start();
+        match trimmed.starts_with('{') {
+            true => Self::parse_json(trimmed),
+            false => Self::parse_pairs(payload),
+        }
+    }
+
+    fn push(&mut self, key: String, value: Option<String>) {
+        self.keys.push(key);
+        self.values.push(value);
+    }
+
+    fn parse_pairs(payload: &str) -> Result<Self, String> {
+        let mut res = Self::default();
+        let mut chars = payload.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                return Ok(res);
+            }
+            let mut key = String::new();
+            loop {
+                match chars.next() {
+                    Some('=') => break,
+                    Some(c) if !c.is_whitespace() => key.push(c),
+                    _ => return Err(format!("missing `=` after key `{}`", key)),
+                }
+            }
+            if key.is_empty() {
+                return Err("empty key".to_string());
+            }
+            let mut value = String::new();
+            if chars.next_if_eq(&'\'').is_some() {
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => value.push(c),
+                            None => return Err("unterminated escape sequence".to_string()),
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err(format!("unterminated value of key `{}`", key)),
+                    }
+                }
+                if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                    return Err(format!("unexpected character after value of key `{}`", key));
+                }
+                res.push(key, Some(value));
+            } else {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    value.push(c);
+                }
+                res.push(key, Some(value).filter(|v| !v.is_empty()));
+            }
+        }
+    }
+
+    fn parse_json(payload: &str) -> Result<Self, String> {
+        let mut parser = JsonParser {
+            input: payload,
+            chars: payload.chars(),
+        };
+        let mut res = Self::default();
+        parser.expect('{')?;
+        if parser.peek() != Some('}') {
+            loop {
+                parser.expect('"')?;
+                let key = parser.string()?;
+                parser.expect(':')?;
+                let value = parser.value()?;
+                res.push(key, value);
+                match parser.next() {
+                    Some(',') => {}
+                    Some('}') => break,
+                    _ => return Err(parser.error("expected `,` or `}`")),
+                }
+            }
+        } else {
+            parser.next();
+        }
+        match parser.next() {
+            None => Ok(res),
+            Some(_) => Err(parser.error("trailing characters")),
+        }
+    }
+}
+
+/// A parser for the subset of JSON needed to split an object into its members.
+struct JsonParser<'a> {
+    input: &'a str,
+    chars: Chars<'a>,
+}
+
+impl JsonParser<'_> {
+    fn pos(&self) -> usize {
+        self.input.len() - self.chars.as_str().len()
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("invalid JSON at byte {}: {}", self.pos(), message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.chars.as_str();
+        self.chars = rest.trim_start_matches([' ', '\t', '\n', '\r']).chars();
+    }
+
+    /// Returns the next non-whitespace character without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.clone().next()
+    }
+
+    /// Consumes the next non-whitespace character.
+    fn next(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.next() {
+            Some(n) if n == c => Ok(()),
+            _ => Err(self.error(&format!("expected `{}`", c))),
+        }
+    }
+
+    /// Parses a value and returns its text representation.
+    fn value(&mut self) -> Result<Option<String>, String> {
+        match self.peek() {
+            Some('"') => {
+                self.chars.next();
+                self.string().map(Some)
+            }
+            Some('{' | '[') => {
+                let start = self.pos();
+                self.skip_nested()?;
+                Ok(Some(self.input[start..self.pos()].to_string()))
+            }
+            Some(_) => {
+                let rest = self.chars.as_str();
+                let len = rest
+                    .find([',', '}', ']', ' ', '\t', '\n', '\r'])
+                    .unwrap_or(rest.len());
+                let token = &rest[..len];
+                let valid = match token {
+                    "true" | "false" => true,
+                    "null" => {
+                        self.chars = rest[len..].chars();
+                        return Ok(None);
+                    }
+                    _ => {
+                        token.starts_with(|c: char| c == '-' || c.is_ascii_digit())
+                            && token
+                                .chars()
+                                .all(|c| matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+                            && token.parse::<f64>().is_ok()
+                    }
+                };
+                if !valid {
+                    return Err(self.error("invalid value"));
+                }
+                self.chars = rest[len..].chars();
+                Ok(Some(token.to_string()))
+            }
+            None => Err(self.error("expected value")),
+        }
+    }
+
+    /// Skips a nested object or array.
+    fn skip_nested(&mut self) -> Result<(), String> {
+        let mut closing = Vec::new();
+        loop {
+            match self.chars.next() {
+                Some('{') => closing.push('}'),
+                Some('[') => closing.push(']'),
+                Some(c @ ('}' | ']')) => {
+                    if closing.pop() != Some(c) {
+                        return Err(self.error("mismatched bracket"));
+                    }
+                    if closing.is_empty() {
+                        return Ok(());
+                    }
+                }
+                Some('"') => {
+                    self.string()?;
+                }
+                Some(_) => {}
+                None => return Err(self.error("unterminated value")),
+            }
+        }
+    }
+
+    /// Parses the rest of a string after the opening quote.
+    fn string(&mut self) -> Result<String, String> {
+        let mut res = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(res),
+                Some('\\') => {
+                    let c = match self.chars.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+                    res.push(c);
+                }
+                Some(c) if c < ' ' => return Err(self.error("control character in string")),
+                Some(c) => res.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = match high {
+            0xd800..=0xdbff => {
+                if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+                    return Err(self.error("unpaired surrogate"));
+                }
+                let low = self.hex4()?;
+                if !(0xdc00..=0xdfff).contains(&low) {
+                    return Err(self.error("unpaired surrogate"));
+                }
+                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+            }
+            _ => high,
+        };
+        char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let rest = self.chars.as_str();
+        let code = rest
+            .get(..4)
+            .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(|hex| u32::from_str_radix(hex, 16).unwrap())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.chars = rest[4..].chars();
+        Ok(code)
+    }
+}
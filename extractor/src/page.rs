@@ -0,0 +1,376 @@
+//! Keyset pagination.
+//!
+//! Instead of skipping rows with `OFFSET`, each page continues after the last row of the
+//! previous page:
+//!
+//! ```sql
+//! SELECT * FROM (...) page WHERE ("created", "id") > ($1, $2) ORDER BY "created", "id" LIMIT $3
+//! ```
+//!
+//! The ordering is defined by the fields of a type implementing [`Paginate`]. The position
+//! after a page is stored in an opaque [`Cursor`] that can be converted to and from a
+//! string.
+//!
+//! The sort keys should be `NOT NULL` and unique in combination, for example by including
+//! the primary key. Otherwise rows can be skipped.
+//!
+//! # Examples
+//!
+//! ```
+//! # use tokio_postgres::Client;
+//! # use tokio_postgres_extractor::{Columns, Error, Extract, Paginate};
+//! # use tokio_postgres_extractor::page::{Cursor, Page};
+//! #[derive(Columns, Extract, Paginate)]
+//! struct User {
+//!     #[column(sort_key)]
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! async fn list_users(client: &Client, after: Option<&str>) -> Result<Vec<User>, Error> {
+//!     let after: Option<Cursor> = after.map(|c| c.parse().unwrap());
+//!     let page = Page::<User>::load(client, "select * from users", &[], after.as_ref(), 50)
+//!         .await?;
+//!     if let Some(next) = &page.next {
+//!         println!("next page: {}", next);
+//!     }
+//!     Ok(page.items)
+//! }
+//! ```
+
+use {
+    crate::{copy::quote_ident, Error, ExtractOwned},
+    bytes::{BufMut, BytesMut},
+    std::{
+        error::Error as StdError,
+        fmt::{self, Display, Formatter, Write},
+        str::{self, FromStr},
+    },
+    tokio_postgres::{
+        types::{IsNull, ToSql, Type, WrongType},
+        GenericClient,
+    },
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A type whose rows can be paginated by a tuple of sort keys.
+///
+/// This trait is usually derived with the [`Paginate`](macro@crate::Paginate) proc macro.
+pub trait Paginate: ExtractOwned {
+    /// The names of the sort key columns, in the order in which they are compared.
+    const SORT_KEYS: &'static [&'static str];
+
+    /// Returns the types of the sort key columns.
+    fn sort_key_types() -> Vec<Type>;
+
+    /// Returns the values of the sort key columns.
+    fn sort_key_values(&self) -> Vec<&(dyn ToSql + Sync)>;
+}
+
+/// Returns a statement loading a page from the rows of `query`.
+///
+/// `query` is used as a subquery and can therefore contain its own filters. It is assumed
+/// to use the parameters `$1` to `$num_params`. If `after` is true, the next parameters
+/// are the values of the cursor. The last parameter is the limit.
+///
+/// The values of the cursor are cast to the types of their columns.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::{Columns, Extract, Paginate};
+/// # use tokio_postgres_extractor::page::page_sql;
+/// #[derive(Columns, Extract, Paginate)]
+/// struct Post {
+///     #[column(sort_key)]
+///     created: i64,
+///     #[column(sort_key)]
+///     id: i32,
+/// }
+///
+/// assert_eq!(
+///     page_sql::<Post>("select * from posts where author = $1", 1, true),
+///     r#"SELECT * FROM (select * from posts where author = $1) page WHERE ("created", "id") > ($2::"pg_catalog"."int8", $3::"pg_catalog"."int4") ORDER BY "created", "id" LIMIT $4"#,
+/// );
+/// ```
+pub fn page_sql<T: Paginate>(query: &str, num_params: usize, after: bool) -> String {
+    let keys: Vec<_> = T::SORT_KEYS.iter().map(|c| quote_ident(c)).collect();
+    let keys = keys.join(", ");
+    let mut res = format!("SELECT * FROM ({}) page", query);
+    let mut param = num_params;
+    if after {
+        let _ = write!(res, " WHERE ({}) > (", keys);
+        for (idx, ty) in T::sort_key_types().iter().enumerate() {
+            if idx > 0 {
+                res.push_str(", ");
+            }
+            param += 1;
+            let _ = write!(
+                res,
+                "${}::{}.{}",
+                param,
+                quote_ident(ty.schema()),
+                quote_ident(ty.name()),
+            );
+        }
+        res.push(')');
+    }
+    let _ = write!(res, " ORDER BY {} LIMIT ${}", keys, param + 1);
+    res
+}
+
+/// A page of rows.
+#[derive(Clone, Debug)]
+pub struct Page<T> {
+    /// The rows of the page.
+    pub items: Vec<T>,
+    /// The position after the last row, if there are more rows.
+    pub next: Option<Cursor>,
+}
+
+impl<T> Page<T>
+where
+    T: Paginate,
+{
+    /// Loads up to `limit` rows of `query` following the cursor.
+    ///
+    /// If `after` is `None`, the first page is loaded. The statement is described in
+    /// [`page_sql`] and `params` are the parameters of `query`.
+    ///
+    /// One additional row is loaded to determine whether there is a next page.
+    pub async fn load<C>(
+        client: &C,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        after: Option<&Cursor>,
+        limit: u32,
+    ) -> Result<Self, Error>
+    where
+        C: GenericClient,
+    {
+        let sql = page_sql::<T>(query, params.len(), after.is_some());
+        let types = T::sort_key_types();
+        let values = match after {
+            Some(cursor) => cursor.raw_values(&types)?,
+            None => vec![],
+        };
+        let fetch = i64::from(limit) + 1;
+        let mut all_params = params.to_vec();
+        all_params.extend(values.iter().map(|v| v as &(dyn ToSql + Sync)));
+        all_params.push(&fetch);
+        let rows = client.query(&sql, &all_params).await?;
+        let mut columns = None;
+        let items = rows
+            .iter()
+            .take(limit as usize)
+            .map(|row| T::try_extract(&mut columns, row))
+            .collect::<Result<Vec<_>, _>>()?;
+        let next = match items.last() {
+            Some(last) if rows.len() > limit as usize => Some(Cursor::new(last)?),
+            _ => None,
+        };
+        Ok(Self { items, next })
+    }
+}
+
+/// The position after a row.
+///
+/// The cursor contains the types and encoded values of the sort keys of the row. It can be
+/// converted to a string with [`Display`] and back with [`FromStr`]. The format of the
+/// string is unspecified.
+///
+/// [`Page::load`] returns [`Error::InvalidCursor`] if the types of the cursor differ from
+/// the types of the sort keys or if a value does not have the size of its type.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Cursor {
+    values: Vec<CursorValue>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+struct CursorValue {
+    /// The OID of the type of the sort key.
+    oid: u32,
+    value: Option<Vec<u8>>,
+}
+
+impl Cursor {
+    /// Creates a cursor pointing after the row.
+    ///
+    /// If a sort key cannot be encoded, [`Error::Conversion`] is returned with the index of
+    /// the sort key.
+    pub fn new<T: Paginate>(row: &T) -> Result<Self, Error> {
+        let mut buf = BytesMut::new();
+        let values = row
+            .sort_key_values()
+            .into_iter()
+            .zip(T::sort_key_types())
+            .enumerate()
+            .map(|(column, (value, ty))| {
+                buf.clear();
+                let value = match value.to_sql_checked(&ty, &mut buf) {
+                    Ok(IsNull::Yes) => None,
+                    Ok(IsNull::No) => Some(buf.to_vec()),
+                    Err(error) => return Err(Error::Conversion { column, error }),
+                };
+                Ok(CursorValue {
+                    oid: ty.oid(),
+                    value,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { values })
+    }
+
+    /// Returns the values of the cursor after checking them against the sort key types.
+    fn raw_values<'a>(&'a self, types: &'a [Type]) -> Result<Vec<RawValue<'a>>, Error> {
+        if self.values.len() != types.len() {
+            return Err(Error::InvalidCursor);
+        }
+        self.values
+            .iter()
+            .zip(types)
+            .map(|(value, ty)| {
+                if value.oid != ty.oid() {
+                    return Err(Error::InvalidCursor);
+                }
+                let value = value.value.as_deref();
+                if let (Some(value), Some(len)) = (value, fixed_len(ty)) {
+                    if value.len() != len {
+                        return Err(Error::InvalidCursor);
+                    }
+                }
+                Ok(RawValue { ty, value })
+            })
+            .collect()
+    }
+}
+
+/// Returns the size of the binary representation of fixed-size types.
+fn fixed_len(ty: &Type) -> Option<usize> {
+    let len = match *ty {
+        Type::BOOL | Type::CHAR => 1,
+        Type::INT2 => 2,
+        Type::INT4 | Type::OID | Type::FLOAT4 | Type::DATE => 4,
+        Type::INT8 | Type::FLOAT8 | Type::TIMESTAMP | Type::TIMESTAMPTZ | Type::TIME => 8,
+        Type::UUID => 16,
+        _ => return None,
+    };
+    Some(len)
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut write = |bytes: &[u8]| bytes.iter().try_for_each(|b| write!(f, "{:02x}", b));
+        for value in &self.values {
+            write(&value.oid.to_be_bytes())?;
+            match &value.value {
+                Some(value) => {
+                    write(&(value.len() as i32).to_be_bytes())?;
+                    write(value)?;
+                }
+                None => write(&(-1i32).to_be_bytes())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ParseCursorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s
+            .as_bytes()
+            .chunks(2)
+            .map(|hex| {
+                str::from_utf8(hex)
+                    .ok()
+                    .filter(|hex| hex.len() == 2)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or(ParseCursorError(()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut rest = &bytes[..];
+        let mut values = vec![];
+        while !rest.is_empty() {
+            let oid = u32::from_be_bytes(take(&mut rest)?);
+            let len = i32::from_be_bytes(take(&mut rest)?);
+            let value = match len {
+                0.. if rest.len() >= len as usize => {
+                    let (value, tail) = rest.split_at(len as usize);
+                    rest = tail;
+                    Some(value.to_vec())
+                }
+                0.. => return Err(ParseCursorError(())),
+                _ => None,
+            };
+            values.push(CursorValue { oid, value });
+        }
+        Ok(Self { values })
+    }
+}
+
+/// Removes the first `N` bytes from `rest`.
+fn take<const N: usize>(rest: &mut &[u8]) -> Result<[u8; N], ParseCursorError> {
+    if rest.len() < N {
+        return Err(ParseCursorError(()));
+    }
+    let (bytes, tail) = rest.split_at(N);
+    *rest = tail;
+    Ok(bytes.try_into().unwrap())
+}
+
+/// An error returned when parsing a [`Cursor`] fails.
+#[derive(Debug)]
+pub struct ParseCursorError(());
+
+impl Display for ParseCursorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid cursor")
+    }
+}
+
+impl StdError for ParseCursorError {}
+
+/// A value that has already been encoded in the binary format.
+#[derive(Debug)]
+struct RawValue<'a> {
+    /// The type of the sort key.
+    ty: &'a Type,
+    value: Option<&'a [u8]>,
+}
+
+impl ToSql for RawValue<'_> {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        match self.value {
+            Some(value) => {
+                out.put_slice(value);
+                Ok(IsNull::No)
+            }
+            None => Ok(IsNull::Yes),
+        }
+    }
+
+    /// The type is checked in [`ToSql::to_sql_checked`] instead since it depends on the
+    /// value.
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    fn to_sql_checked(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        if (ty.schema(), ty.name()) != (self.ty.schema(), self.ty.name()) {
+            return Err(Box::new(WrongType::new::<Self>(ty.clone())));
+        }
+        self.to_sql(ty, out)
+    }
+}
@@ -0,0 +1,118 @@
+use {
+    crate::{
+        page::{Cursor, Page},
+        tests::connect,
+        Columns, Error, Extract, Paginate,
+    },
+    tokio_postgres::Client,
+};
+
+#[derive(Debug, PartialEq, Columns, Extract, Paginate)]
+struct Post {
+    #[column(sort_key)]
+    group: i32,
+    #[column(sort_key, name = "Id")]
+    id: i64,
+    title: String,
+}
+
+async fn setup() -> Client {
+    let client = connect().await;
+    client
+        .batch_execute(
+            r#"create temporary table posts ("group" int4, "Id" int8, title text);
+               insert into posts select i % 3, i, 't' || i from generate_series(1, 10) i;"#,
+        )
+        .await
+        .unwrap();
+    client
+}
+
+#[tokio::test]
+async fn pages() {
+    let client = setup().await;
+    let query = "select * from posts where \"Id\" > $1";
+    let mut after = None;
+    let mut ids = vec![];
+    loop {
+        let page = Page::<Post>::load(&client, query, &[&1i64], after.as_ref(), 4)
+            .await
+            .unwrap();
+        assert!(page.items.len() <= 4);
+        ids.extend(page.items.iter().map(|p| (p.group, p.id)));
+        match page.next {
+            Some(next) => after = Some(next.to_string().parse::<Cursor>().unwrap()),
+            None => break,
+        }
+    }
+    assert_eq!(
+        ids,
+        [
+            (0, 3),
+            (0, 6),
+            (0, 9),
+            (1, 4),
+            (1, 7),
+            (1, 10),
+            (2, 2),
+            (2, 5),
+            (2, 8),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn exact() {
+    let client = setup().await;
+    let page = Page::<Post>::load(&client, "select * from posts", &[], None, 10)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 10);
+    assert!(page.next.is_none());
+}
+
+#[test]
+fn cursor() {
+    let post = Post {
+        group: 1,
+        id: 2,
+        title: String::new(),
+    };
+    let cursor = Cursor::new(&post).unwrap();
+    let s = cursor.to_string();
+    assert_eq!(
+        s,
+        "00000017000000040000000100000014000000080000000000000002"
+    );
+    assert_eq!(s.parse::<Cursor>().unwrap(), cursor);
+    assert!("0".parse::<Cursor>().is_err());
+    assert!("zz".parse::<Cursor>().is_err());
+    assert!("00000017".parse::<Cursor>().is_err());
+    assert!("0000001700000004".parse::<Cursor>().is_err());
+    assert!("000000170000000400".parse::<Cursor>().is_err());
+}
+
+#[tokio::test]
+async fn invalid_cursor() {
+    let client = setup().await;
+    let load = |cursor: &str| {
+        let cursor = cursor.parse::<Cursor>().unwrap();
+        let client = &client;
+        async move { Page::<Post>::load(client, "select * from posts", &[], Some(&cursor), 1).await }
+    };
+    for cursor in [
+        // Too few sort keys.
+        "00000017ffffffff",
+        // Swapped types.
+        "00000014000000080000000000000002000000170000000400000001",
+        // Wrong size of the int8 value.
+        "000000170000000400000001000000140000000400000002",
+    ] {
+        let res = load(cursor).await;
+        assert!(matches!(res, Err(Error::InvalidCursor)), "{}", cursor);
+    }
+    let page = load("00000017000000040000000100000014ffffffff")
+        .await
+        .unwrap();
+    assert_eq!(page.items[0].group, 2);
+}
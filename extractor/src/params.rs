@@ -0,0 +1,164 @@
+//! Named parameters.
+//!
+//! Statements can refer to parameters by name with `:name`. [`NamedQuery`] replaces the
+//! names with positional parameters and binds them to the fields of a type implementing
+//! [`Params`]. If a name occurs multiple times, all occurrences refer to the same
+//! parameter.
+//!
+//! Names are not replaced in string literals, quoted identifiers, dollar-quoted strings,
+//! comments, and `::` casts. Array slices such as `a[1:n]` are not recognized and must be
+//! written with whitespace after the colon.
+//!
+//! The statement can be rewritten at compile time with the
+//! [`named_query!`](crate::named_query) macro, which also checks that every name is a
+//! parameter of the type, or at runtime with [`NamedQuery::parse`].
+//!
+//! # Examples
+//!
+//! ```
+//! # use tokio_postgres::{Client, Error, Row};
+//! # use tokio_postgres_extractor::{named_query, Params};
+//! #[derive(Params)]
+//! struct UserQuery<'a> {
+//!     id: i32,
+//!     org: &'a str,
+//! }
+//!
+//! async fn get_user(client: &Client, org: &str) -> Result<Row, Error> {
+//!     let query = named_query!(
+//!         UserQuery,
+//!         "select * from users where id = :id and org = :org and name <> ':id'"
+//!     );
+//!     assert_eq!(
+//!         query.sql(),
+//!         "select * from users where id = $1 and org = $2 and name <> ':id'",
+//!     );
+//!     let params = UserQuery { id: 1, org };
+//!     client.query_one(query.sql(), &query.params(&params)).await
+//! }
+//! ```
+
+use {
+    crate::Error,
+    std::{
+        borrow::Cow,
+        fmt::{self, Debug, Formatter},
+        marker::PhantomData,
+    },
+    tokio_postgres::types::ToSql,
+    tokio_postgres_extractor_internal::rewrite_named_params,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A type whose fields are the parameters of a statement.
+///
+/// This trait is usually derived with the [`Params`](macro@crate::Params) proc macro.
+pub trait Params {
+    /// The names of the parameters.
+    const NAMES: &'static [&'static str];
+
+    /// Returns the values of the parameters in the order of [`Self::NAMES`].
+    fn values(&self) -> Vec<&(dyn ToSql + Sync)>;
+}
+
+/// A statement whose named parameters have been replaced with positional parameters.
+///
+/// Construct it using [`NamedQuery::parse`] or the [`named_query!`](crate::named_query)
+/// macro.
+pub struct NamedQuery<'a, P> {
+    sql: Cow<'a, str>,
+    /// The index in `P::NAMES` of each positional parameter.
+    indices: Cow<'a, [usize]>,
+    _phantom: PhantomData<fn(&P)>,
+}
+
+impl<'a, P> NamedQuery<'a, P>
+where
+    P: Params,
+{
+    /// Rewrites the statement.
+    ///
+    /// Returns an error if a name is not a parameter of `P`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_postgres_extractor::Params;
+    /// # use tokio_postgres_extractor::params::NamedQuery;
+    /// #[derive(Params)]
+    /// struct Search {
+    ///     name: String,
+    /// }
+    ///
+    /// let query = NamedQuery::<Search>::parse(
+    ///     "select * from users where name = :name::text or nick = :name -- :nick",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     query.sql(),
+    ///     "select * from users where name = $1::text or nick = $1 -- :nick",
+    /// );
+    ///
+    /// assert!(NamedQuery::<Search>::parse("select :nick").is_err());
+    /// ```
+    pub fn parse(sql: &str) -> Result<Self, Error> {
+        let (sql, names) = rewrite_named_params(sql);
+        let indices = names
+            .iter()
+            .map(|&name| match P::NAMES.iter().position(|&n| n == name) {
+                Some(idx) => Ok(idx),
+                None => Err(Error::UnknownParameter {
+                    name: name.to_string(),
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            sql: Cow::Owned(sql),
+            indices: Cow::Owned(indices),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the statement with positional parameters.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Returns the parameters in the form expected by
+    /// [`Client::query`](tokio_postgres::Client::query).
+    pub fn params<'p>(&self, params: &'p P) -> Vec<&'p (dyn ToSql + Sync)> {
+        let values = params.values();
+        self.indices.iter().map(|&idx| values[idx]).collect()
+    }
+}
+
+impl<P> NamedQuery<'static, P> {
+    pub(crate) const fn from_static(sql: &'static str, indices: &'static [usize]) -> Self {
+        Self {
+            sql: Cow::Borrowed(sql),
+            indices: Cow::Borrowed(indices),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<P> Clone for NamedQuery<'_, P> {
+    fn clone(&self) -> Self {
+        Self {
+            sql: self.sql.clone(),
+            indices: self.indices.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<P> Debug for NamedQuery<'_, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedQuery")
+            .field("sql", &self.sql)
+            .field("indices", &self.indices)
+            .finish()
+    }
+}
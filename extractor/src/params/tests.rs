@@ -0,0 +1,55 @@
+use {
+    crate::{named_query, params::NamedQuery, tests::connect, Error, Params},
+    tokio_postgres::types::ToSql,
+};
+
+#[derive(Params)]
+struct Query<'a> {
+    id: i32,
+    #[column(name = "org")]
+    organization: &'a str,
+    #[column(skip)]
+    #[allow(dead_code)]
+    cached: bool,
+}
+
+#[test]
+fn parse() {
+    let query = NamedQuery::<Query>::parse("select :org, :id, :org").unwrap();
+    assert_eq!(query.sql(), "select $1, $2, $1");
+    let params = Query {
+        id: 1,
+        organization: "o",
+        cached: false,
+    };
+    let values = query.params(&params);
+    assert_eq!(format!("{:?}", values), r#"["o", 1]"#);
+    let res = NamedQuery::<Query>::parse("select :cached");
+    assert!(matches!(res, Err(Error::UnknownParameter { name }) if name == "cached"));
+}
+
+#[tokio::test]
+async fn named() {
+    let query = named_query!(
+        Query,
+        "select :id + 1 as x, :org::text as y, :id::int8 as z"
+    );
+    assert_eq!(
+        query.sql(),
+        "select $1 + 1 as x, $2::text as y, $1::int8 as z"
+    );
+    let params = Query {
+        id: 1,
+        organization: "o",
+        cached: true,
+    };
+    let values: Vec<&(dyn ToSql + Sync)> = query.params(&params);
+    let row = connect()
+        .await
+        .query_one(query.sql(), &values)
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i32>(0), 2);
+    assert_eq!(row.get::<_, &str>(1), "o");
+    assert_eq!(row.get::<_, i64>(2), 1);
+}
@@ -0,0 +1,192 @@
+//! Extraction from portals.
+//!
+//! [`Transaction::query_raw`](tokio_postgres::Transaction::query_raw) receives rows as fast
+//! as the server produces them. For large scans, [`TransactionExtractExt::extract_cursor`]
+//! binds the statement to a portal instead and fetches a fixed number of rows whenever the
+//! previous batch has been consumed.
+//!
+//! # Examples
+//!
+//! ```
+//! # use futures_util::TryStreamExt;
+//! # use tokio_postgres::{Error, Transaction};
+//! # use tokio_postgres_extractor::{Columns, Extract};
+//! # use tokio_postgres_extractor::portal::TransactionExtractExt;
+//! #[derive(Columns, Extract)]
+//! struct User {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! async fn count_names(txn: &Transaction<'_>) -> Result<usize, Error> {
+//!     txn.extract_cursor::<User, _>("select * from users", &[], 1000)
+//!         .try_fold(0, |n, user| async move { Ok(n + user.name.len()) })
+//!         .await
+//! }
+//! ```
+
+use {
+    crate::{portal::sealed::Sealed, ExtractOwned},
+    futures_core::Stream,
+    std::{
+        future::Future,
+        pin::Pin,
+        task::{ready, Context, Poll},
+    },
+    tokio_postgres::{types::ToSql, Error, Portal, RowStream, ToStatement, Transaction},
+};
+
+#[cfg(test)]
+mod tests;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+
+/// A [`Stream`] producing `T`s from a portal.
+///
+/// Construct it using [`TransactionExtractExt::extract_cursor`].
+///
+/// # Panics
+///
+/// The stream panics if [`Extract::extract`][crate::Extract::extract] panics.
+pub struct PortalExtractStream<'a, T>
+where
+    T: ExtractOwned,
+{
+    transaction: &'a Transaction<'a>,
+    fetch_size: i32,
+    state: State<'a>,
+    columns: Option<T::Columns>,
+}
+
+enum State<'a> {
+    Binding(BoxFuture<'a, Portal>),
+    Fetching(Portal, BoxFuture<'a, RowStream>),
+    Streaming(Portal, Pin<Box<RowStream>>),
+    Done,
+}
+
+impl<'a, T> Stream for PortalExtractStream<'a, T>
+where
+    T: ExtractOwned,
+{
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let slf = self.get_mut();
+        loop {
+            let res = match &mut slf.state {
+                State::Binding(future) => ready!(future.as_mut().poll(cx)).map(|portal| {
+                    let fetch = slf.fetch(&portal);
+                    State::Fetching(portal, fetch)
+                }),
+                State::Fetching(portal, future) => ready!(future.as_mut().poll(cx))
+                    .map(|rows| State::Streaming(portal.clone(), Box::pin(rows))),
+                State::Streaming(portal, rows) => match ready!(rows.as_mut().poll_next(cx)) {
+                    Some(Ok(row)) => {
+                        return Poll::Ready(Some(Ok(T::extract(&mut slf.columns, &row))))
+                    }
+                    Some(Err(e)) => Err(e),
+                    // The server reports the number of rows only once the portal has been
+                    // exhausted.
+                    None if rows.rows_affected().is_some() => Ok(State::Done),
+                    None => {
+                        let portal = portal.clone();
+                        let fetch = slf.fetch(&portal);
+                        Ok(State::Fetching(portal, fetch))
+                    }
+                },
+                State::Done => return Poll::Ready(None),
+            };
+            match res {
+                Ok(state) => slf.state = state,
+                Err(e) => {
+                    slf.state = State::Done;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T> PortalExtractStream<'a, T>
+where
+    T: ExtractOwned,
+{
+    fn fetch(&self, portal: &Portal) -> BoxFuture<'a, RowStream> {
+        let transaction = self.transaction;
+        let portal = portal.clone();
+        let fetch_size = self.fetch_size;
+        Box::pin(async move { transaction.query_portal_raw(&portal, fetch_size).await })
+    }
+}
+
+/// Extension trait for extracting from a [`Transaction`].
+pub trait TransactionExtractExt: Sealed {
+    /// Returns a [`Stream`] over the rows of the statement.
+    ///
+    /// The statement is bound to a portal and up to `fetch_size` rows are fetched at a
+    /// time. The next batch is only requested once the previous batch has been consumed.
+    /// The mapping between fields and columns is computed once for all batches.
+    ///
+    /// If an error occurs, the stream produces the error and ends.
+    ///
+    /// # Panics
+    ///
+    /// The stream panics if `fetch_size` is not positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use futures_util::TryStreamExt;
+    /// # use tokio_postgres::{Error, Transaction};
+    /// # use tokio_postgres_extractor::{Columns, Extract};
+    /// # use tokio_postgres_extractor::portal::TransactionExtractExt;
+    /// #[derive(Columns, Extract)]
+    /// struct User {
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// async fn extract_users(txn: &Transaction<'_>) -> Result<Vec<User>, Error> {
+    ///     txn.extract_cursor("select * from users where id > $1", &[&10], 100)
+    ///         .try_collect()
+    ///         .await
+    /// }
+    /// ```
+    fn extract_cursor<'a, T, S>(
+        &'a self,
+        statement: &'a S,
+        params: &'a [&'a (dyn ToSql + Sync)],
+        fetch_size: i32,
+    ) -> PortalExtractStream<'a, T>
+    where
+        T: ExtractOwned,
+        S: ?Sized + ToStatement + Sync;
+}
+
+impl TransactionExtractExt for Transaction<'_> {
+    fn extract_cursor<'a, T, S>(
+        &'a self,
+        statement: &'a S,
+        params: &'a [&'a (dyn ToSql + Sync)],
+        fetch_size: i32,
+    ) -> PortalExtractStream<'a, T>
+    where
+        T: ExtractOwned,
+        S: ?Sized + ToStatement + Sync,
+    {
+        assert!(fetch_size > 0, "fetch_size must be positive");
+        PortalExtractStream {
+            transaction: self,
+            fetch_size,
+            state: State::Binding(Box::pin(self.bind(statement, params))),
+            columns: None,
+        }
+    }
+}
+
+impl Sealed for Transaction<'_> {}
+
+mod sealed {
+    pub trait Sealed {}
+}
@@ -0,0 +1,47 @@
+use {
+    crate::{portal::TransactionExtractExt, tests::connect, Columns, Extract},
+    futures_util::{StreamExt, TryStreamExt},
+};
+
+#[derive(Debug, PartialEq, Columns, Extract)]
+struct X {
+    x: i32,
+}
+
+#[tokio::test]
+async fn batches() {
+    let mut client = connect().await;
+    let txn = client.transaction().await.unwrap();
+    for (n, fetch_size) in [(0, 3), (5, 1), (6, 3), (7, 3), (7, 100)] {
+        let res: Vec<X> = txn
+            .extract_cursor("select x from generate_series(1, $1) x", &[&n], fetch_size)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(res, (1..=n).map(|x| X { x }).collect::<Vec<_>>());
+    }
+}
+
+#[tokio::test]
+async fn lazy() {
+    let mut client = connect().await;
+    let txn = client.transaction().await.unwrap();
+    let mut stream = txn.extract_cursor::<X, _>(
+        "select 1 / (3 - x) as x from generate_series(1, 10) x",
+        &[],
+        2,
+    );
+    assert_eq!(stream.try_next().await.unwrap(), Some(X { x: 0 }));
+    assert_eq!(stream.try_next().await.unwrap(), Some(X { x: 1 }));
+    assert!(stream.try_next().await.is_err());
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn bind_error() {
+    let mut client = connect().await;
+    let txn = client.transaction().await.unwrap();
+    let mut stream = txn.extract_cursor::<X, _>("select x from missing", &[], 2);
+    assert!(stream.try_next().await.is_err());
+    assert!(stream.next().await.is_none());
+}
@@ -0,0 +1,109 @@
+use {
+    crate::{Columns, Error, Extract},
+    std::{
+        fmt::{Debug, Formatter},
+        marker::PhantomData,
+    },
+    tokio_postgres::Row,
+};
+
+/// A type that can extract instances of another type from a [`Row`].
+///
+/// This trait is implemented by the [`Extract`](macro@Extract) proc macro for mirror
+/// definitions annotated with `#[columns(remote = "...")]`. It allows types defined in
+/// other crates to be extracted without implementing [`Extract`] for them.
+///
+/// Most of the time you will not use this trait directly but the [`Remote`] wrapper.
+pub trait ExtractAs<'row, T>: Columns {
+    /// Extracts an instance of `T` from a [`Row`] and a mapping between the fields and
+    /// columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Row::get`] panics.
+    fn extract_as_with_columns(columns: &<Self as Columns>::Columns, row: &'row Row) -> T;
+
+    /// Extracts an instance of `T` from a [`Row`] and a mapping between the fields and
+    /// columns, returning an error instead of panicking if a column cannot be decoded.
+    ///
+    /// The default implementation calls [`ExtractAs::extract_as_with_columns`] and never
+    /// returns an error.
+    fn try_extract_as_with_columns(
+        columns: &<Self as Columns>::Columns,
+        row: &'row Row,
+    ) -> Result<T, Error> {
+        Ok(Self::extract_as_with_columns(columns, row))
+    }
+}
+
+/// A `T` extracted via the mirror definition `M`.
+///
+/// This type implements [`Columns`] and [`Extract`] if `M` implements [`ExtractAs<T>`].
+/// It can therefore be used with all functions of this crate.
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres::Row;
+/// # use tokio_postgres_extractor::{Columns, Extract, Remote, RowExtractExt};
+/// mod domain {
+///     pub struct User {
+///         pub id: i32,
+///         pub name: String,
+///     }
+/// }
+///
+/// #[derive(Columns, Extract)]
+/// #[columns(remote = "domain::User")]
+/// struct UserDef {
+///     id: i32,
+///     name: String,
+/// }
+///
+/// fn map_user(row: &Row) -> domain::User {
+///     row.extract_once::<Remote<UserDef, _>>().into_inner()
+/// }
+/// ```
+pub struct Remote<M, T> {
+    value: T,
+    _mirror: PhantomData<fn() -> M>,
+}
+
+impl<M, T> Remote<M, T> {
+    /// Wraps a value.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            _mirror: PhantomData,
+        }
+    }
+
+    /// Returns the extracted value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<M, T: Debug> Debug for Remote<M, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.value, f)
+    }
+}
+
+impl<M: Columns, T> Columns for Remote<M, T> {
+    type Columns = M::Columns;
+
+    fn try_columns(row: &Row) -> Result<Self::Columns, Error> {
+        M::try_columns(row)
+    }
+}
+
+impl<'row, M: ExtractAs<'row, T>, T> Extract<'row> for Remote<M, T> {
+    fn extract_with_columns(columns: &Self::Columns, row: &'row Row) -> Self {
+        Self::new(M::extract_as_with_columns(columns, row))
+    }
+
+    fn try_extract_with_columns(columns: &Self::Columns, row: &'row Row) -> Result<Self, Error> {
+        M::try_extract_as_with_columns(columns, row).map(Self::new)
+    }
+}
@@ -0,0 +1,498 @@
+//! Decoding of logical replication messages.
+//!
+//! The `pgoutput` plugin streams the changes of a publication as binary messages wrapped in
+//! [`XLogData`] messages. [`ChangeDecoder`] parses these messages and extracts the rows of
+//! `INSERT`, `UPDATE`, and `DELETE` statements into a type implementing [`ExtractText`].
+//!
+//! The mapping between fields and columns is computed whenever the server describes a table
+//! with a [`Relation`] message. Rows are transmitted in the text format and each value is
+//! parsed with [`FromStr`](std::str::FromStr).
+//!
+//! Only protocol version 1 without the `binary` option is supported. Messages that are not
+//! described in this module, such as `TRUNCATE` or the messages of streamed transactions,
+//! are skipped.
+//!
+//! # Old rows
+//!
+//! Unless the table uses `REPLICA IDENTITY FULL`, the old row of an `UPDATE` is only sent
+//! if the key changed, and the old row of an `UPDATE` or `DELETE` only contains the key
+//! columns. All other columns are `NULL`. Fields of such columns should therefore be
+//! nullable or annotated with `null_default`. Otherwise decoding these messages fails with
+//! [`Error::Null`].
+//!
+//! TOASTed values that were not changed by an `UPDATE` are not sent. They are taken from
+//! the old row if it contains them. Otherwise decoding fails.
+//!
+//! # Examples
+//!
+//! ```
+//! # use tokio_postgres_extractor::{Columns, Error, ExtractText};
+//! # use tokio_postgres_extractor::replication::{Change, ChangeDecoder, Message, XLogData};
+//! #[derive(Columns, ExtractText)]
+//! struct User {
+//!     id: i32,
+//!     name: Option<String>,
+//! }
+//!
+//! fn handle(decoder: &mut ChangeDecoder<User>, message: &[u8]) -> Result<(), Error> {
+//!     let xlog = XLogData::parse(message)?;
+//!     if let Some(Message::Change { change, .. }) = decoder.decode(xlog.data)? {
+//!         match change {
+//!             Change::Insert(user) => println!("created user {}", user.id),
+//!             Change::Update { new, .. } => println!("updated user {}", new.id),
+//!             Change::Delete(user) => println!("deleted user {}", user.id),
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//!
+//! let mut decoder = ChangeDecoder::<User>::for_table("public", "users");
+//! ```
+
+use {
+    crate::{
+        text::{sealed::Sealed, TextRow},
+        Error, ExtractText,
+    },
+    std::{collections::HashMap, str, sync::Arc},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// An `XLogData` message of the streaming replication protocol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct XLogData<'a> {
+    /// The position of the data in the WAL.
+    pub wal_start: u64,
+    /// The current end of the WAL on the server.
+    pub wal_end: u64,
+    /// The time at which the message was sent, in microseconds since 2000-01-01 UTC.
+    pub timestamp: i64,
+    /// The message of the output plugin.
+    pub data: &'a [u8],
+}
+
+impl<'a> XLogData<'a> {
+    /// Parses an `XLogData` message, including the leading `w`.
+    pub fn parse(message: &'a [u8]) -> Result<Self, Error> {
+        let mut reader = Reader(message);
+        if reader.u8()? != b'w' {
+            return Err(error("not an XLogData message"));
+        }
+        Ok(Self {
+            wal_start: reader.u64()?,
+            wal_end: reader.u64()?,
+            timestamp: reader.i64()?,
+            data: reader.0,
+        })
+    }
+}
+
+/// The start of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Begin {
+    /// The LSN of the commit record of the transaction.
+    pub final_lsn: u64,
+    /// The commit time, in microseconds since 2000-01-01 UTC.
+    pub timestamp: i64,
+    /// The ID of the transaction.
+    pub xid: u32,
+}
+
+/// The end of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Commit {
+    /// The flags of the commit. Currently unused.
+    pub flags: u8,
+    /// The LSN of the commit.
+    pub commit_lsn: u64,
+    /// The end LSN of the transaction.
+    pub end_lsn: u64,
+    /// The commit time, in microseconds since 2000-01-01 UTC.
+    pub timestamp: i64,
+}
+
+/// The description of a table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Relation {
+    /// The OID of the table.
+    pub id: u32,
+    /// The schema of the table. This is empty for `pg_catalog`.
+    pub namespace: String,
+    /// The name of the table.
+    pub name: String,
+    /// The replica identity setting of the table, e.g. `d` for `DEFAULT` or `f` for
+    /// `FULL`.
+    pub replica_identity: u8,
+    /// The columns of the table.
+    pub columns: Vec<RelationColumn>,
+}
+
+/// A column of a [`Relation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RelationColumn {
+    /// Whether the column is part of the replica identity.
+    pub key: bool,
+    /// The name of the column.
+    pub name: String,
+    /// The OID of the type of the column.
+    pub type_oid: u32,
+    /// The type modifier of the column.
+    pub type_modifier: i32,
+}
+
+/// A change of a row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change<T> {
+    /// A row was inserted.
+    Insert(T),
+    /// A row was updated.
+    Update {
+        /// The old row, if it was sent by the server.
+        ///
+        /// See the [module documentation](self#old-rows) for the columns it contains.
+        old: Option<T>,
+        /// The new row.
+        new: T,
+    },
+    /// A row was deleted.
+    ///
+    /// See the [module documentation](self#old-rows) for the columns the row contains.
+    Delete(T),
+}
+
+/// A decoded message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message<T> {
+    /// A transaction started.
+    Begin(Begin),
+    /// A transaction ended.
+    Commit(Commit),
+    /// A table was described.
+    ///
+    /// This message is sent before the first change of the table and after the table was
+    /// altered.
+    Relation(Arc<Relation>),
+    /// A row was changed.
+    Change {
+        /// The table of the row.
+        relation: Arc<Relation>,
+        /// The change.
+        change: Change<T>,
+    },
+}
+
+/// A decoder of `pgoutput` messages.
+pub struct ChangeDecoder<T>
+where
+    T: ExtractText,
+{
+    table: Option<(String, String)>,
+    relations: HashMap<u32, (Arc<Relation>, Mapping<T::Columns>)>,
+}
+
+/// The mapping of a relation.
+enum Mapping<C> {
+    /// The changes of the relation are skipped.
+    Skipped,
+    /// The fields are mapped to the columns of the relation.
+    Columns(C),
+    /// The fields could not be mapped to the columns of the relation.
+    Failed(String),
+}
+
+impl<T> ChangeDecoder<T>
+where
+    T: ExtractText,
+{
+    /// Creates a decoder that extracts the changes of all tables.
+    pub fn new() -> Self {
+        Self {
+            table: None,
+            relations: HashMap::new(),
+        }
+    }
+
+    /// Creates a decoder that only extracts the changes of one table.
+    ///
+    /// The changes of other tables are skipped.
+    pub fn for_table(namespace: &str, name: &str) -> Self {
+        Self {
+            table: Some((namespace.to_string(), name.to_string())),
+            relations: HashMap::new(),
+        }
+    }
+
+    /// Decodes the message of the output plugin.
+    ///
+    /// Returns `None` if the message is skipped.
+    ///
+    /// Returns [`Error::Replication`] if the message is malformed. Returns
+    /// [`Error::Mapping`] if the fields of `T` cannot be mapped to the columns of a table
+    /// whose changes are decoded. The table is still registered in this case and its
+    /// changes return the same error until the server describes the table again. Returns
+    /// [`Error::Null`] if the old row of an `UPDATE` or `DELETE` only contains the key
+    /// columns and `T` has a non-nullable field for another column.
+    pub fn decode(&mut self, data: &[u8]) -> Result<Option<Message<T>>, Error> {
+        let mut reader = Reader(data);
+        let message = match reader.u8()? {
+            b'B' => Message::Begin(Begin {
+                final_lsn: reader.u64()?,
+                timestamp: reader.i64()?,
+                xid: reader.u32()?,
+            }),
+            b'C' => Message::Commit(Commit {
+                flags: reader.u8()?,
+                commit_lsn: reader.u64()?,
+                end_lsn: reader.u64()?,
+                timestamp: reader.i64()?,
+            }),
+            b'R' => {
+                let relation = Arc::new(reader.relation()?);
+                let mapping = match &self.table {
+                    Some((namespace, name))
+                        if (namespace, name) != (&relation.namespace, &relation.name) =>
+                    {
+                        Mapping::Skipped
+                    }
+                    _ => {
+                        let tuple = Tuple {
+                            relation: &relation,
+                            values: vec![None; relation.columns.len()],
+                        };
+                        match T::try_text_columns(&tuple) {
+                            Ok(columns) => Mapping::Columns(columns),
+                            Err(e) => Mapping::Failed(format!(
+                                "table `{}.{}`: {}",
+                                relation.namespace, relation.name, e
+                            )),
+                        }
+                    }
+                };
+                let failed = match &mapping {
+                    Mapping::Failed(message) => Some(message.clone()),
+                    _ => None,
+                };
+                self.relations
+                    .insert(relation.id, (relation.clone(), mapping));
+                if let Some(message) = failed {
+                    return Err(Error::Mapping { message });
+                }
+                Message::Relation(relation)
+            }
+            tag @ (b'I' | b'U' | b'D') => {
+                let id = reader.u32()?;
+                let (relation, columns) = match self.relations.get(&id) {
+                    Some((relation, Mapping::Columns(columns))) => (relation, columns),
+                    Some((_, Mapping::Skipped)) => return Ok(None),
+                    Some((_, Mapping::Failed(message))) => {
+                        return Err(Error::Mapping {
+                            message: message.clone(),
+                        })
+                    }
+                    None => return Err(error(&format!("unknown relation {}", id))),
+                };
+                let mut old = None;
+                let mut kind = reader.u8()?;
+                if tag != b'I' && matches!(kind, b'K' | b'O') {
+                    old = Some(reader.tuple(relation, None)?);
+                    kind = match tag {
+                        b'U' => reader.u8()?,
+                        _ => b'N',
+                    };
+                }
+                let extract = |tuple: &Tuple| T::extract_text_with_columns(columns, tuple);
+                let change = match tag {
+                    b'D' => match &old {
+                        Some(old) => Change::Delete(extract(old)?),
+                        None => return Err(error("missing old row")),
+                    },
+                    _ => {
+                        if kind != b'N' {
+                            return Err(error("missing new row"));
+                        }
+                        let new = extract(&reader.tuple(relation, old.as_ref())?)?;
+                        match tag {
+                            b'I' => Change::Insert(new),
+                            _ => Change::Update {
+                                old: old.as_ref().map(extract).transpose()?,
+                                new,
+                            },
+                        }
+                    }
+                };
+                Message::Change {
+                    relation: relation.clone(),
+                    change,
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(message))
+    }
+}
+
+impl<T> Default for ChangeDecoder<T>
+where
+    T: ExtractText,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The values of a row of a [`Relation`].
+struct Tuple<'a> {
+    relation: &'a Relation,
+    values: Vec<Option<&'a str>>,
+}
+
+impl Sealed for Tuple<'_> {}
+
+impl TextRow for Tuple<'_> {
+    fn num_columns(&self) -> usize {
+        self.relation.columns.len()
+    }
+
+    fn column_name(&self, idx: usize) -> &str {
+        &self.relation.columns[idx].name
+    }
+
+    fn get(&self, idx: usize) -> Option<&str> {
+        self.values[idx]
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < n {
+            return Err(error("unexpected end of message"));
+        }
+        let (bytes, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, Error> {
+        self.array().map(i16::from_be_bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, Error> {
+        self.array().map(i32::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn i64(&mut self) -> Result<i64, Error> {
+        self.array().map(i64::from_be_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        self.array().map(u64::from_be_bytes)
+    }
+
+    fn str(&mut self, n: usize) -> Result<&'a str, Error> {
+        str::from_utf8(self.bytes(n)?).map_err(|e| error(&e.to_string()))
+    }
+
+    fn cstr(&mut self) -> Result<String, Error> {
+        let len = self
+            .0
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| error("unterminated string"))?;
+        let s = self.str(len)?.to_string();
+        self.0 = &self.0[1..];
+        Ok(s)
+    }
+
+    fn relation(&mut self) -> Result<Relation, Error> {
+        let id = self.u32()?;
+        let namespace = self.cstr()?;
+        let name = self.cstr()?;
+        let replica_identity = self.u8()?;
+        let num_columns = self.i16()?;
+        let columns = (0..num_columns)
+            .map(|_| {
+                Ok(RelationColumn {
+                    key: self.u8()? & 1 != 0,
+                    name: self.cstr()?,
+                    type_oid: self.u32()?,
+                    type_modifier: self.i32()?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Relation {
+            id,
+            namespace,
+            name,
+            replica_identity,
+            columns,
+        })
+    }
+
+    /// Reads a row. Unchanged TOASTed values are taken from `old`.
+    fn tuple<'b>(
+        &mut self,
+        relation: &'b Relation,
+        old: Option<&Tuple<'a>>,
+    ) -> Result<Tuple<'b>, Error>
+    where
+        'a: 'b,
+    {
+        let num_columns = self.i16()?;
+        if num_columns as usize != relation.columns.len() {
+            let msg = format!(
+                "expected {} values, found {}",
+                relation.columns.len(),
+                num_columns,
+            );
+            return Err(error(&msg));
+        }
+        let mut values = Vec::with_capacity(relation.columns.len());
+        for (idx, column) in relation.columns.iter().enumerate() {
+            let value = match self.u8()? {
+                b'n' => None,
+                b't' => {
+                    let len = self.i32()?;
+                    Some(self.str(len as usize)?)
+                }
+                b'u' => match old.and_then(|old| old.values[idx]) {
+                    Some(value) => Some(value),
+                    None => {
+                        let msg = format!("unchanged TOASTed value in column `{}`", column.name);
+                        return Err(error(&msg));
+                    }
+                },
+                b'b' => return Err(error("values in the binary format are not supported")),
+                kind => return Err(error(&format!("invalid value kind {}", kind))),
+            };
+            values.push(value);
+        }
+        Ok(Tuple { relation, values })
+    }
+}
+
+fn error(message: &str) -> Error {
+    Error::Replication {
+        message: message.to_string(),
+    }
+}
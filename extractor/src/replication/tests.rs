@@ -0,0 +1,233 @@
+use {
+    crate::{
+        replication::{Change, ChangeDecoder, Message, XLogData},
+        Columns, Error, ExtractText,
+    },
+    std::fmt::Debug,
+};
+
+#[derive(Debug, PartialEq, Columns, ExtractText)]
+struct User {
+    id: i32,
+    name: Option<String>,
+    note: Option<String>,
+}
+
+fn user(id: i32, name: Option<&str>, note: Option<&str>) -> User {
+    User {
+        id,
+        name: name.map(|n| n.to_string()),
+        note: note.map(|n| n.to_string()),
+    }
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// The messages produced by `pgoutput` for
+///
+/// ```sql
+/// create table cap_users (id int4 primary key, name text, note text);
+/// insert into cap_users values (1, 'alice', null);
+/// update cap_users set name = 'bob' where id = 1;
+/// update cap_users set id = 2 where id = 1;
+/// delete from cap_users where id = 2;
+/// ```
+const CAPTURED: &[&str] = &[
+    "4200000000018c64600003011c328ab985000003c9",
+    "52000041d77075626c6963006361705f7573657273006400030169640000000017ffffffff006e616d650000000019ffffffff006e6f74650000000019ffffffff",
+    "49000041d74e00037400000001317400000005616c6963656e",
+    "430000000000018c646000000000018c64900003011c328ab985",
+    "4200000000018c64e00003011c328abd2c000003ca",
+    "55000041d74e00037400000001317400000003626f626e",
+    "430000000000018c64e000000000018c65100003011c328abd2c",
+    "4200000000018c65a80003011c328abea3000003cb",
+    "55000041d74b00037400000001316e6e4e00037400000001327400000003626f626e",
+    "430000000000018c65a800000000018c65d80003011c328abea3",
+    "4200000000018c66180003011c328abf6c000003cc",
+    "44000041d74b00037400000001326e6e",
+    "430000000000018c661800000000018c66480003011c328abf6c",
+];
+
+fn decode_all<T: ExtractText + Debug>(decoder: &mut ChangeDecoder<T>) -> Vec<Option<Message<T>>> {
+    CAPTURED
+        .iter()
+        .map(|m| decoder.decode(&hex(m)).unwrap())
+        .collect()
+}
+
+fn changes<T>(messages: Vec<Option<Message<T>>>) -> Vec<Change<T>> {
+    messages
+        .into_iter()
+        .filter_map(|m| match m {
+            Some(Message::Change { change, .. }) => Some(change),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn captured() {
+    let mut decoder = ChangeDecoder::<User>::new();
+    let messages = decode_all(&mut decoder);
+    let Some(Message::Begin(begin)) = &messages[0] else {
+        panic!("{:?}", messages[0]);
+    };
+    assert_eq!(begin.xid, 969);
+    assert_eq!(begin.final_lsn, 0x18c6460);
+    let Some(Message::Relation(relation)) = &messages[1] else {
+        panic!("{:?}", messages[1]);
+    };
+    assert_eq!(relation.id, 16855);
+    assert_eq!(relation.namespace, "public");
+    assert_eq!(relation.name, "cap_users");
+    assert_eq!(relation.replica_identity, b'd');
+    let columns: Vec<_> = relation
+        .columns
+        .iter()
+        .map(|c| (c.key, c.name.as_str(), c.type_oid))
+        .collect();
+    assert_eq!(
+        columns,
+        [(true, "id", 23), (false, "name", 25), (false, "note", 25)]
+    );
+    let Some(Message::Commit(commit)) = &messages[3] else {
+        panic!("{:?}", messages[3]);
+    };
+    assert_eq!(commit.commit_lsn, 0x18c6460);
+    assert_eq!(commit.end_lsn, 0x18c6490);
+    assert_eq!(
+        changes(messages),
+        [
+            Change::Insert(user(1, Some("alice"), None)),
+            Change::Update {
+                old: None,
+                new: user(1, Some("bob"), None),
+            },
+            Change::Update {
+                old: Some(user(1, None, None)),
+                new: user(2, Some("bob"), None),
+            },
+            Change::Delete(user(2, None, None)),
+        ]
+    );
+}
+
+#[test]
+fn other_table() {
+    #[derive(Debug, Columns, ExtractText)]
+    struct Other {
+        #[allow(dead_code)]
+        other: i32,
+    }
+
+    let mut decoder = ChangeDecoder::<Other>::for_table("public", "other");
+    let messages = decode_all(&mut decoder);
+    assert!(matches!(messages[1], Some(Message::Relation(_))));
+    assert!(messages[2].is_none());
+    assert!(changes(messages).is_empty());
+}
+
+#[test]
+fn unchanged_toast() {
+    let mut decoder = ChangeDecoder::<User>::new();
+    decoder.decode(&hex(CAPTURED[1])).unwrap();
+    let res = decoder.decode(&hex("55000041d74e000374000000013174000000016175"));
+    assert!(matches!(res, Err(Error::Replication { .. })));
+    let res = decoder.decode(&hex("55000041d74f0003740000000131740000000161740000000178\
+         4e000374000000013174000000016275"));
+    let Ok(Some(Message::Change { change, .. })) = res else {
+        panic!("{:?}", res);
+    };
+    assert_eq!(
+        change,
+        Change::Update {
+            old: Some(user(1, Some("a"), Some("x"))),
+            new: user(1, Some("b"), Some("x")),
+        }
+    );
+}
+
+#[test]
+fn malformed() {
+    let mut decoder = ChangeDecoder::<User>::new();
+    let res = decoder.decode(&hex(CAPTURED[2]));
+    assert!(matches!(res, Err(Error::Replication { .. })));
+    decoder.decode(&hex(CAPTURED[1])).unwrap();
+    let insert = hex(CAPTURED[2]);
+    let res = decoder.decode(&insert[..insert.len() - 1]);
+    assert!(matches!(res, Err(Error::Replication { .. })));
+    let res = decoder.decode(&hex("49000041d74e0001740000000131"));
+    assert!(matches!(res, Err(Error::Replication { .. })));
+    let res = decoder.decode(&hex("49000041d74e00037400000001787400000001616e"));
+    assert!(matches!(res, Err(Error::Conversion { column: 0, .. })));
+    assert!(matches!(decoder.decode(b"T"), Ok(None)));
+}
+
+#[test]
+fn missing_column() {
+    #[derive(Debug, Columns, ExtractText)]
+    struct Email {
+        #[allow(dead_code)]
+        id: i32,
+        #[allow(dead_code)]
+        email: String,
+    }
+
+    let mut decoder = ChangeDecoder::<Email>::new();
+    for data in &CAPTURED[1..3] {
+        match decoder.decode(&hex(data)) {
+            Err(Error::Mapping { message }) => assert_eq!(
+                message,
+                "table `public.cap_users`: There is no column named `email`"
+            ),
+            res => panic!("{:?}", res),
+        }
+    }
+    let mut decoder = ChangeDecoder::<Email>::for_table("public", "other");
+    assert!(decoder.decode(&hex(CAPTURED[1])).is_ok());
+}
+
+#[test]
+fn key_only_old_row() {
+    #[derive(Debug, PartialEq, Columns, ExtractText)]
+    struct Named {
+        id: i32,
+        name: String,
+    }
+
+    let mut decoder = ChangeDecoder::<Named>::new();
+    let res: Vec<_> = CAPTURED.iter().map(|m| decoder.decode(&hex(m))).collect();
+    let Ok(Some(Message::Change { change, .. })) = &res[5] else {
+        panic!("{:?}", res[5]);
+    };
+    assert_eq!(
+        change,
+        &Change::Update {
+            old: None,
+            new: Named {
+                id: 1,
+                name: "bob".to_string(),
+            },
+        }
+    );
+    assert!(matches!(res[8], Err(Error::Null { column: 1 })));
+    assert!(matches!(res[11], Err(Error::Null { column: 1 })));
+}
+
+#[test]
+fn xlog_data() {
+    let mut message = hex("77000000000000000100000000000000020000000000000003");
+    message.extend_from_slice(b"data");
+    let xlog = XLogData::parse(&message).unwrap();
+    assert_eq!(xlog.wal_start, 1);
+    assert_eq!(xlog.wal_end, 2);
+    assert_eq!(xlog.timestamp, 3);
+    assert_eq!(xlog.data, b"data");
+    assert!(XLogData::parse(&message[..10]).is_err());
+    assert!(XLogData::parse(b"k").is_err());
+}
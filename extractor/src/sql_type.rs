@@ -0,0 +1,93 @@
+use tokio_postgres::types::Type;
+
+/// A Rust type with a corresponding Postgres type.
+///
+/// This trait is used by code that has to name the Postgres types of fields before any
+/// rows are available, for example, the [`CopyOut`](macro@crate::CopyOut) proc macro. It
+/// is implemented for the common types supported by `tokio_postgres`. Fields of other types
+/// can specify their type with `#[column(sql_type = Type::JSONB)]`.
+pub trait SqlType {
+    /// Returns the Postgres type.
+    fn sql_type() -> Type;
+}
+
+/// A [`SqlType`] whose values can be elements of a Postgres array.
+///
+/// This trait is used to map fields to array parameters, for example, by the
+/// [`Unnest`](macro@crate::Unnest) proc macro. It is implemented for the types that
+/// implement [`SqlType`] except for arrays, since Postgres does not have arrays of arrays.
+pub trait SqlArrayType: SqlType {
+    /// Returns the Postgres type of arrays of this type.
+    fn sql_array_type() -> Type;
+}
+
+macro_rules! simple {
+    ($($ty:ty => $sql:ident / $array:ident,)*) => {
+        $(
+            impl SqlType for $ty {
+                fn sql_type() -> Type {
+                    Type::$sql
+                }
+            }
+
+            impl SqlArrayType for $ty {
+                fn sql_array_type() -> Type {
+                    Type::$array
+                }
+            }
+        )*
+    };
+}
+
+simple! {
+    bool => BOOL / BOOL_ARRAY,
+    i8 => CHAR / CHAR_ARRAY,
+    i16 => INT2 / INT2_ARRAY,
+    i32 => INT4 / INT4_ARRAY,
+    i64 => INT8 / INT8_ARRAY,
+    u32 => OID / OID_ARRAY,
+    f32 => FLOAT4 / FLOAT4_ARRAY,
+    f64 => FLOAT8 / FLOAT8_ARRAY,
+    String => TEXT / TEXT_ARRAY,
+    str => TEXT / TEXT_ARRAY,
+    Box<str> => TEXT / TEXT_ARRAY,
+    Vec<u8> => BYTEA / BYTEA_ARRAY,
+    [u8] => BYTEA / BYTEA_ARRAY,
+    Box<[u8]> => BYTEA / BYTEA_ARRAY,
+}
+
+impl<T: SqlType + ?Sized> SqlType for &T {
+    fn sql_type() -> Type {
+        T::sql_type()
+    }
+}
+
+impl<T: SqlArrayType + ?Sized> SqlArrayType for &T {
+    fn sql_array_type() -> Type {
+        T::sql_array_type()
+    }
+}
+
+impl<T: SqlType> SqlType for Option<T> {
+    fn sql_type() -> Type {
+        T::sql_type()
+    }
+}
+
+impl<T: SqlArrayType> SqlArrayType for Option<T> {
+    fn sql_array_type() -> Type {
+        T::sql_array_type()
+    }
+}
+
+impl<T: SqlArrayType> SqlType for Vec<T> {
+    fn sql_type() -> Type {
+        T::sql_array_type()
+    }
+}
+
+impl<T: SqlArrayType> SqlType for [T] {
+    fn sql_type() -> Type {
+        T::sql_array_type()
+    }
+}
@@ -0,0 +1,378 @@
+//! Key-based access to tables.
+//!
+//! Types implementing [`Table`] know the name of their table and its primary key. This
+//! allows rows to be loaded and deleted by key. [`BatchLoader`] coalesces concurrent
+//! lookups into a single query.
+//!
+//! # Examples
+//!
+//! ```
+//! # use tokio_postgres::Client;
+//! # use tokio_postgres_extractor::{Columns, Error, Extract, Table};
+//! # use tokio_postgres_extractor::table::BatchLoader;
+//! #[derive(Clone, Columns, Extract, Table)]
+//! #[table(name = "users")]
+//! struct User {
+//!     #[column(primary_key)]
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! async fn load_users(client: &Client) -> Result<(), Error> {
+//!     let user = User::find_by_key(client, &1).await?;
+//!     let users = User::find_many(client, &[1, 2, 3]).await?;
+//!
+//!     // Executes a single query.
+//!     let loader = BatchLoader::<User>::new(client);
+//!     let (a, b) = futures_util::future::join(loader.load(1), loader.load(2)).await;
+//!     Ok(())
+//! }
+//! ```
+
+use {
+    crate::{copy::quote_ident, Error, ExtractOwned},
+    std::{
+        collections::{HashMap, HashSet},
+        future::{poll_fn, Future},
+        hash::Hash,
+        marker::PhantomData,
+        mem,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Waker},
+    },
+    tokio_postgres::{types::ToSql, Client, GenericClient},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A type that is stored in a table with a primary key.
+///
+/// This trait is usually derived with the [`Table`](macro@crate::Table) proc macro.
+pub trait Table: ExtractOwned {
+    /// The name of the table.
+    ///
+    /// The name is inserted verbatim into statements and can therefore contain a schema.
+    const TABLE: &'static str;
+
+    /// The name of the primary key column.
+    const PRIMARY_KEY: &'static str;
+
+    /// The type of the primary key.
+    type Key: ToSql + Sync;
+
+    /// Returns the primary key of the row.
+    fn key(&self) -> &Self::Key;
+
+    /// Loads the row with the key.
+    fn find_by_key<'a, C>(
+        client: &'a C,
+        key: &'a Self::Key,
+    ) -> impl Future<Output = Result<Option<Self>, Error>> + Send + 'a
+    where
+        C: GenericClient + Sync,
+        Self: Send,
+    {
+        async move {
+            let sql = format!(
+                "SELECT * FROM {} WHERE {} = $1",
+                Self::TABLE,
+                quote_ident(Self::PRIMARY_KEY),
+            );
+            match client.query_opt(&sql, &[key]).await? {
+                Some(row) => Self::try_extract_once(&row).map(Some),
+                None => Ok(None),
+            }
+        }
+    }
+
+    /// Loads the rows with the keys.
+    ///
+    /// The rows are returned in an unspecified order. Keys without rows are ignored.
+    fn find_many<'a, C>(
+        client: &'a C,
+        keys: &'a [Self::Key],
+    ) -> impl Future<Output = Result<Vec<Self>, Error>> + Send + 'a
+    where
+        C: GenericClient + Sync,
+        Self: Send,
+    {
+        async move {
+            let sql = format!(
+                "SELECT * FROM {} WHERE {} = ANY($1)",
+                Self::TABLE,
+                quote_ident(Self::PRIMARY_KEY),
+            );
+            let rows = client.query(&sql, &[&keys]).await?;
+            let mut columns = None;
+            rows.iter()
+                .map(|row| Self::try_extract(&mut columns, row))
+                .collect()
+        }
+    }
+
+    /// Deletes the row with the key.
+    ///
+    /// Returns the number of deleted rows.
+    fn delete_by_key<'a, C>(
+        client: &'a C,
+        key: &'a Self::Key,
+    ) -> impl Future<Output = Result<u64, Error>> + Send + 'a
+    where
+        C: GenericClient + Sync,
+    {
+        async move {
+            let sql = format!(
+                "DELETE FROM {} WHERE {} = $1",
+                Self::TABLE,
+                quote_ident(Self::PRIMARY_KEY),
+            );
+            Ok(client.execute(&sql, &[key]).await?)
+        }
+    }
+}
+
+/// A loader that coalesces concurrent lookups by key.
+///
+/// The first call to [`BatchLoader::load`] starts a batch and yields to the executor once.
+/// All calls that are made before it is polled again join the batch. The keys of a batch
+/// are then loaded with a single [`Table::find_many`] query and the rows are routed back
+/// to the callers by key.
+///
+/// If the call that started a batch is dropped before the batch has been loaded, one of
+/// the remaining callers loads the batch instead.
+pub struct BatchLoader<'a, T, C = Client>
+where
+    T: Table,
+{
+    client: &'a C,
+    current: Mutex<Option<Arc<Batch<T>>>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<'a, T, C> BatchLoader<'a, T, C>
+where
+    T: Table + Clone + Send,
+    T::Key: Clone + Eq + Hash + Send,
+    C: GenericClient + Sync,
+{
+    /// Creates a new loader.
+    pub fn new(client: &'a C) -> Self {
+        Self {
+            client,
+            current: Mutex::new(None),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Loads the row with the key.
+    ///
+    /// If loading the batch fails, all callers receive [`Error::Batch`].
+    pub async fn load(&self, key: T::Key) -> Result<Option<T>, Error> {
+        let (batch, leader, waiter) = self.enqueue(&key);
+        let mut runner = Runner {
+            loader: self,
+            batch: &batch,
+            active: leader,
+        };
+        if leader {
+            YieldNow(false).await;
+        }
+        loop {
+            if runner.active {
+                let keys = batch.start(self);
+                let result = T::find_many(self.client, &keys).await;
+                batch.finish(result.map_err(Arc::new));
+                runner.active = false;
+            }
+            match poll_fn(|cx| batch.poll(cx, &key, waiter)).await {
+                Some(result) => return result,
+                None => runner.active = true,
+            }
+        }
+    }
+
+    /// Adds the key to the current batch or starts a new one.
+    ///
+    /// Returns the batch, whether the caller started it, and the index of the caller's
+    /// waker.
+    fn enqueue(&self, key: &T::Key) -> (Arc<Batch<T>>, bool, usize) {
+        let mut current = self.current.lock().unwrap();
+        if let Some(batch) = &*current {
+            let mut inner = batch.state.lock().unwrap();
+            let inner = &mut *inner;
+            if let BatchState::Open(keys) = &mut inner.state {
+                keys.push(key.clone());
+                inner.wakers.push(None);
+                return (batch.clone(), false, inner.wakers.len() - 1);
+            }
+        }
+        let batch = Arc::new(Batch {
+            state: Mutex::new(BatchInner {
+                state: BatchState::Open(vec![key.clone()]),
+                wakers: vec![None],
+            }),
+        });
+        *current = Some(batch.clone());
+        (batch, true, 0)
+    }
+
+    /// Stops new keys from joining the batch.
+    fn close(&self, batch: &Arc<Batch<T>>) {
+        let mut current = self.current.lock().unwrap();
+        if let Some(c) = &*current {
+            if Arc::ptr_eq(c, batch) {
+                *current = None;
+            }
+        }
+    }
+}
+
+struct Batch<T>
+where
+    T: Table,
+{
+    state: Mutex<BatchInner<T>>,
+}
+
+struct BatchInner<T>
+where
+    T: Table,
+{
+    state: BatchState<T>,
+    /// The wakers of the pending callers, indexed by the order in which they joined.
+    wakers: Vec<Option<Waker>>,
+}
+
+impl<T> BatchInner<T>
+where
+    T: Table,
+{
+    fn wake_all(&mut self) {
+        self.wakers
+            .iter_mut()
+            .filter_map(Option::take)
+            .for_each(Waker::wake);
+    }
+}
+
+enum BatchState<T>
+where
+    T: Table,
+{
+    /// The batch accepts new keys.
+    Open(Vec<T::Key>),
+    /// The batch is being loaded.
+    Running(Vec<T::Key>),
+    /// The caller loading the batch was dropped.
+    Orphaned(Vec<T::Key>),
+    Done(Result<HashMap<T::Key, T>, Arc<Error>>),
+}
+
+impl<T> Batch<T>
+where
+    T: Table + Clone + Send,
+    T::Key: Clone + Eq + Hash + Send,
+{
+    /// Marks the batch as running and returns the deduplicated keys.
+    fn start<C>(self: &Arc<Self>, loader: &BatchLoader<'_, T, C>) -> Vec<T::Key>
+    where
+        C: GenericClient + Sync,
+    {
+        loader.close(self);
+        let mut inner = self.state.lock().unwrap();
+        let mut keys = match mem::replace(&mut inner.state, BatchState::Running(vec![])) {
+            BatchState::Open(keys) | BatchState::Running(keys) => keys,
+            _ => unreachable!(),
+        };
+        let mut seen = HashSet::new();
+        keys.retain(|key| seen.insert(key.clone()));
+        inner.state = BatchState::Running(keys.clone());
+        keys
+    }
+
+    fn finish(&self, result: Result<Vec<T>, Arc<Error>>) {
+        let result = result.map(|rows| {
+            rows.into_iter()
+                .map(|row| (row.key().clone(), row))
+                .collect()
+        });
+        let mut inner = self.state.lock().unwrap();
+        inner.state = BatchState::Done(result);
+        inner.wake_all();
+    }
+
+    /// Returns the result for the key or `None` if the caller has to load the batch.
+    fn poll(
+        &self,
+        cx: &mut Context<'_>,
+        key: &T::Key,
+        waiter: usize,
+    ) -> Poll<Option<Result<Option<T>, Error>>> {
+        let mut inner = self.state.lock().unwrap();
+        match &mut inner.state {
+            BatchState::Done(Ok(rows)) => Poll::Ready(Some(Ok(rows.get(key).cloned()))),
+            BatchState::Done(Err(e)) => Poll::Ready(Some(Err(Error::Batch(e.clone())))),
+            BatchState::Orphaned(keys) => {
+                inner.state = BatchState::Running(mem::take(keys));
+                Poll::Ready(None)
+            }
+            BatchState::Open(_) | BatchState::Running(_) => {
+                match &mut inner.wakers[waiter] {
+                    Some(waker) if waker.will_wake(cx.waker()) => {}
+                    waker => *waker = Some(cx.waker().clone()),
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Hands the batch over to the remaining callers if the caller loading it is dropped.
+struct Runner<'a, 'b, T, C>
+where
+    T: Table + Clone + Send,
+    T::Key: Clone + Eq + Hash + Send,
+    C: GenericClient + Sync,
+{
+    loader: &'a BatchLoader<'b, T, C>,
+    batch: &'a Arc<Batch<T>>,
+    active: bool,
+}
+
+impl<T, C> Drop for Runner<'_, '_, T, C>
+where
+    T: Table + Clone + Send,
+    T::Key: Clone + Eq + Hash + Send,
+    C: GenericClient + Sync,
+{
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+        self.loader.close(self.batch);
+        let mut inner = self.batch.state.lock().unwrap();
+        let keys = match &mut inner.state {
+            BatchState::Open(keys) | BatchState::Running(keys) => mem::take(keys),
+            _ => return,
+        };
+        inner.state = BatchState::Orphaned(keys);
+        inner.wake_all();
+    }
+}
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
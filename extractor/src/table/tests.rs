@@ -0,0 +1,120 @@
+use {
+    crate::{
+        table::{BatchLoader, BatchState},
+        tests::connect,
+        Columns, Error, Extract, Table,
+    },
+    futures_util::{
+        future::{join, join3},
+        poll,
+    },
+    std::pin::pin,
+};
+
+#[derive(Clone, Debug, PartialEq, Columns, Extract, Table)]
+#[table(name = "items")]
+struct Item {
+    #[column(primary_key, name = "Id")]
+    id: i32,
+    name: String,
+}
+
+fn item(id: i32) -> Item {
+    Item {
+        id,
+        name: format!("n{}", id),
+    }
+}
+
+async fn setup() -> tokio_postgres::Client {
+    let client = connect().await;
+    client
+        .batch_execute(
+            r#"create temporary table items ("Id" int4 primary key, name text, extra text);
+               insert into items select i, 'n' || i from generate_series(1, 10) i;"#,
+        )
+        .await
+        .unwrap();
+    client
+}
+
+#[tokio::test]
+async fn find_and_delete() {
+    let client = setup().await;
+    assert_eq!(Item::find_by_key(&client, &3).await.unwrap(), Some(item(3)));
+    assert_eq!(Item::find_by_key(&client, &11).await.unwrap(), None);
+    let mut items = Item::find_many(&client, &[5, 1, 11]).await.unwrap();
+    items.sort_by_key(|i| i.id);
+    assert_eq!(items, [item(1), item(5)]);
+    assert_eq!(Item::delete_by_key(&client, &1).await.unwrap(), 1);
+    assert_eq!(Item::delete_by_key(&client, &1).await.unwrap(), 0);
+    assert_eq!(Item::find_by_key(&client, &1).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn missing_column() {
+    #[derive(Clone, Debug, Columns, Extract, Table)]
+    #[table(name = "items")]
+    #[allow(dead_code)]
+    struct Other {
+        #[column(primary_key, name = "Id")]
+        id: i32,
+        missing: String,
+    }
+
+    let client = setup().await;
+    let res = Other::find_by_key(&client, &1).await;
+    assert!(matches!(res, Err(Error::Mapping { .. })));
+    let res = Other::find_many(&client, &[1, 2]).await;
+    assert!(matches!(res, Err(Error::Mapping { .. })));
+}
+
+#[tokio::test]
+async fn batch_loader() {
+    let client = setup().await;
+    let loader = BatchLoader::new(&client);
+    let (a, b, c) = join3(loader.load(2), loader.load(11), loader.load(2)).await;
+    assert_eq!(a.unwrap(), Some(item(2)));
+    assert_eq!(b.unwrap(), None);
+    assert_eq!(c.unwrap(), Some(item(2)));
+    assert_eq!(loader.load(4).await.unwrap(), Some(item(4)));
+}
+
+#[tokio::test]
+async fn batch_loader_dropped_leader() {
+    let client = setup().await;
+    let loader = BatchLoader::<Item>::new(&client);
+    let mut a = Box::pin(loader.load(1));
+    let mut b = pin!(loader.load(2));
+    assert!(poll!(&mut a).is_pending());
+    assert!(poll!(&mut b).is_pending());
+    {
+        let current = loader.current.lock().unwrap();
+        let inner = current.as_ref().unwrap().state.lock().unwrap();
+        assert!(matches!(&inner.state, BatchState::Open(keys) if keys == &[1, 2]));
+    }
+    drop(a);
+    assert_eq!(b.await.unwrap(), Some(item(2)));
+}
+
+#[tokio::test]
+async fn batch_loader_repeated_poll() {
+    let client = setup().await;
+    let loader = BatchLoader::<Item>::new(&client);
+    let mut a = pin!(loader.load(1));
+    let mut b = pin!(loader.load(2));
+    assert!(poll!(&mut a).is_pending());
+    for _ in 0..5 {
+        assert!(poll!(&mut b).is_pending());
+    }
+    {
+        let current = loader.current.lock().unwrap();
+        let inner = current.as_ref().unwrap().state.lock().unwrap();
+        assert_eq!(inner.wakers.len(), 2);
+        assert!(inner.wakers[0].is_none());
+        assert!(inner.wakers[1].is_some());
+    }
+    let (a, b) = join(a, b).await;
+    assert_eq!(a.unwrap(), Some(item(1)));
+    assert_eq!(b.unwrap(), Some(item(2)));
+}
@@ -1,6 +1,7 @@
 use {
-    crate::{Extract, RowExtractExt},
-    tokio_postgres::{Client, NoTls, Row},
+    crate::{DynValue, DynamicColumns, Error, Extract, Remote, RowExtractExt},
+    std::{collections::HashMap, marker::PhantomData},
+    tokio_postgres::{types::Type, Client, NoTls, Row},
     tokio_postgres_extractor_macros::Columns,
 };
 
@@ -169,3 +170,635 @@ async fn tuple_struct() {
     assert_eq!(x.2, 5);
     assert_eq!(x.3, 4);
 }
+
+#[tokio::test]
+async fn via() {
+    #[derive(Debug, PartialEq)]
+    struct Id(i64);
+
+    impl From<i64> for Id {
+        fn from(id: i64) -> Self {
+            Self(id)
+        }
+    }
+
+    #[derive(Columns, Extract)]
+    struct X {
+        #[column(via = i64)]
+        id: Id,
+        #[column(via = i64)]
+        small: u8,
+    }
+
+    let x: X = row("select 1::int8 id, 2::int8 small").await.extract_once();
+    assert_eq!(x.id, Id(1));
+    assert_eq!(x.small, 2);
+}
+
+#[tokio::test]
+#[should_panic(expected = "error converting column 0")]
+async fn via_error() {
+    #[derive(Columns, Extract)]
+    struct X {
+        #[column(via = i64)]
+        #[allow(dead_code)]
+        small: u8,
+    }
+
+    let _: X = row("select 256::int8 small").await.extract_once();
+}
+
+#[tokio::test]
+async fn with() {
+    fn split(s: &str) -> Vec<String> {
+        s.split(',').map(|s| s.to_string()).collect()
+    }
+
+    #[derive(Columns, Extract)]
+    struct X {
+        #[column(with = split)]
+        tags: Vec<String>,
+        #[column(with = str::len)]
+        len: usize,
+    }
+
+    let row = row("select 'a,b' tags, 'abc' len").await;
+    let x: X = row.extract_once();
+    assert_eq!(x.tags, ["a", "b"]);
+    assert_eq!(x.len, 3);
+}
+
+#[tokio::test]
+async fn with_row() {
+    fn to_string(row: &Row, idx: usize) -> Result<String, tokio_postgres::Error> {
+        match *row.columns()[idx].type_() {
+            Type::INT4 => Ok(row.try_get::<_, i32>(idx)?.to_string()),
+            _ => row.try_get(idx),
+        }
+    }
+
+    fn positive(row: &Row, idx: usize) -> Result<u32, Error> {
+        let value: i32 = row.try_get(idx)?;
+        value.try_into().map_err(|e| Error::Conversion {
+            column: idx,
+            error: Box::new(e),
+        })
+    }
+
+    #[derive(Columns, Extract)]
+    struct X {
+        #[column(with_row = to_string)]
+        a: String,
+        #[column(name = "B", with_row = to_string)]
+        b: String,
+    }
+
+    let x: X = row(r#"select 'x' "B", 1 a"#).await.extract_once();
+    assert_eq!(x.a, "1");
+    assert_eq!(x.b, "x");
+    let x: X = row(r#"select 'y' a, 2 "B""#)
+        .await
+        .try_extract_once()
+        .unwrap();
+    assert_eq!(x.a, "y");
+    assert_eq!(x.b, "2");
+
+    #[derive(Debug, Columns, Extract)]
+    struct Y {
+        #[column(with_row = positive)]
+        #[allow(dead_code)]
+        a: u32,
+    }
+
+    let res = row("select -1 a").await.try_extract_once::<Y>();
+    assert!(matches!(res, Err(Error::Conversion { column: 0, .. })));
+    let res = row("select 'x' a").await.try_extract_once::<Y>();
+    assert!(matches!(res, Err(Error::Postgres(_))));
+}
+
+#[tokio::test]
+async fn try_extract() {
+    #[derive(Columns, Extract)]
+    struct X {
+        x: i32,
+        #[column(via = i64)]
+        y: u8,
+    }
+
+    let x: X = row("select 1 x, 2::int8 y")
+        .await
+        .try_extract_once()
+        .unwrap();
+    assert_eq!(x.x, 1);
+    assert_eq!(x.y, 2);
+
+    let res = row("select 'a' x, 2::int8 y").await.try_extract_once::<X>();
+    assert!(matches!(res, Err(Error::Postgres(_))));
+
+    let res = row("select 1 x").await.try_extract_once::<X>();
+    match res {
+        Err(Error::Mapping { message }) => assert_eq!(message, "There is no column named `y`"),
+        _ => panic!(),
+    }
+    let mut columns = None;
+    let res = row("select 2::int8 y").await.try_extract::<X>(&mut columns);
+    assert!(matches!(res, Err(Error::Mapping { .. })));
+    assert!(columns.is_none());
+
+    let res = row("select 1 x, 256::int8 y").await.try_extract_once::<X>();
+    assert!(matches!(res, Err(Error::Conversion { column: 1, .. })));
+}
+
+#[derive(Columns, Extract)]
+#[extract(finish = Self::finish, validate = Self::validate)]
+struct Period {
+    start: i32,
+    end: i32,
+    #[column(skip)]
+    len: i32,
+}
+
+impl Period {
+    fn finish(&mut self) {
+        self.len = self.end - self.start;
+    }
+
+    fn validate(&self) -> Result<(), &'static str> {
+        match self.start <= self.end {
+            true => Ok(()),
+            false => Err("start is after end"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn validate() {
+    let x: Period = row("select 1 start, 3 end").await.extract_once();
+    assert_eq!(x.len, 2);
+
+    let x: Period = row("select 1 start, 3 end")
+        .await
+        .try_extract_once()
+        .unwrap();
+    assert_eq!(x.len, 2);
+
+    let res = row("select 3 start, 1 end")
+        .await
+        .try_extract_once::<Period>();
+    match res {
+        Err(Error::Validation { ty, error }) => {
+            assert_eq!(ty, "Period");
+            assert_eq!(error.to_string(), "start is after end");
+        }
+        _ => panic!("expected a validation error"),
+    }
+}
+
+#[tokio::test]
+#[should_panic(expected = "validation of `Period` failed: start is after end")]
+async fn validate_panic() {
+    let _: Period = row("select 3 start, 1 end").await.extract_once();
+}
+
+#[tokio::test]
+async fn from_columns() {
+    #[derive(Debug, PartialEq)]
+    struct Money {
+        amount: i64,
+        currency: String,
+    }
+
+    impl Money {
+        fn new(amount: i64, currency: String) -> Self {
+            Self { amount, currency }
+        }
+    }
+
+    fn widen(id: i32) -> i64 {
+        id.into()
+    }
+
+    #[derive(Columns, Extract)]
+    struct X {
+        id: i32,
+        #[column(from_columns("amount", "currency"), with = Money::new)]
+        price: Money,
+        #[column(from_columns("id"), with = widen)]
+        id64: i64,
+    }
+
+    let mut columns = None;
+    let x: X = row("select 'EUR' currency, 1 id, 100::int8 amount")
+        .await
+        .extract(&mut columns);
+    assert_eq!(columns, Some([1, 2, 0, 1]));
+    assert_eq!(x.id, 1);
+    assert_eq!(x.id64, 1);
+    assert_eq!(
+        x.price,
+        Money {
+            amount: 100,
+            currency: "EUR".to_string(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn pattern() {
+    #[derive(Columns, Extract)]
+    struct X {
+        #[column(pattern = "q{}_revenue", range = 1..=4)]
+        revenue: [i64; 4],
+        #[column(pattern = "x{}", range = 0..2)]
+        x: [i32; 2],
+    }
+
+    let x: X = row(
+        "select 1::int8 q1_revenue, 2::int8 q3_revenue, 3::int8 q2_revenue, 4::int8 q4_revenue, \
+         5 x1, 6 x0",
+    )
+    .await
+    .extract_once();
+    assert_eq!(x.revenue, [1, 3, 2, 4]);
+    assert_eq!(x.x, [6, 5]);
+}
+
+#[tokio::test]
+async fn prefix_vec() {
+    #[derive(Columns, Extract)]
+    struct X {
+        id: i32,
+        #[column(prefix_vec = "tag_")]
+        tags: Vec<String>,
+        tag_count: i32,
+    }
+
+    let mut columns = None;
+    let x: X = row("select 'c' tag_10, 1 id, 'a' tag_0, 'b' tag_2, 3 tag_count, 'x' tag_")
+        .await
+        .extract(&mut columns);
+    assert_eq!(
+        columns,
+        Some(DynamicColumns {
+            fixed: [1, 4],
+            dynamic: [vec![2, 3, 0]],
+        })
+    );
+    assert_eq!(x.id, 1);
+    assert_eq!(x.tags, ["a", "b", "c"]);
+    assert_eq!(x.tag_count, 3);
+
+    let x: X = row("select 1 id, 0 tag_count")
+        .await
+        .try_extract_once()
+        .unwrap();
+    assert!(x.tags.is_empty());
+}
+
+#[tokio::test]
+async fn prefix_vec_only() {
+    #[derive(Columns, Extract)]
+    struct X(i32, #[column(prefix_vec = "c")] Vec<i32>);
+
+    let x: X = row("select 1, 2 c1, 3 c0").await.extract_once();
+    assert_eq!(x.0, 1);
+    assert_eq!(x.1, [3, 2]);
+}
+
+#[tokio::test]
+async fn rest() {
+    #[derive(Columns, Extract)]
+    struct X {
+        id: i32,
+        #[column(rest)]
+        extra: HashMap<String, DynValue>,
+    }
+
+    let mut columns = None;
+    let x: X = row("select 'a' name, 1 id, null::int4 age, true admin, 1.5::float8 score")
+        .await
+        .extract(&mut columns);
+    assert_eq!(
+        columns,
+        Some(DynamicColumns {
+            fixed: [1],
+            dynamic: [vec![0, 2, 3, 4]],
+        })
+    );
+    assert_eq!(x.id, 1);
+    assert_eq!(x.extra.len(), 4);
+    assert_eq!(x.extra["name"], DynValue::Text("a".to_string()));
+    assert_eq!(x.extra["age"], DynValue::Null);
+    assert_eq!(x.extra["admin"], DynValue::Bool(true));
+    assert_eq!(x.extra["score"], DynValue::Float8(1.5));
+}
+
+#[tokio::test]
+async fn rest_vec() {
+    #[derive(Columns, Extract)]
+    struct X {
+        #[column(rest)]
+        extra: Vec<(String, i32)>,
+        #[column(prefix_vec = "x")]
+        x: Vec<i32>,
+    }
+
+    let x: X = row("select 1 b, 2 x1, 3 a")
+        .await
+        .try_extract_once()
+        .unwrap();
+    assert_eq!(x.extra, [("b".to_string(), 1), ("a".to_string(), 3)]);
+    assert_eq!(x.x, [2]);
+}
+
+#[tokio::test]
+async fn null_default() {
+    #[derive(Debug, PartialEq)]
+    struct Id(i64);
+
+    impl From<i64> for Id {
+        fn from(id: i64) -> Self {
+            Self(id)
+        }
+    }
+
+    #[derive(Columns, Extract)]
+    struct X {
+        #[column(null_default)]
+        balance: i64,
+        #[column(null_default = "unknown".to_string())]
+        name: String,
+        #[column(null_default = Id(-1), via = i64)]
+        id: Id,
+    }
+
+    let x: X = row("select null::int8 balance, null::text name, null::int8 id")
+        .await
+        .extract_once();
+    assert_eq!(x.balance, 0);
+    assert_eq!(x.name, "unknown");
+    assert_eq!(x.id, Id(-1));
+
+    let x: X = row("select 1::int8 balance, 'a' name, 2::int8 id")
+        .await
+        .try_extract_once()
+        .unwrap();
+    assert_eq!(x.balance, 1);
+    assert_eq!(x.name, "a");
+    assert_eq!(x.id, Id(2));
+}
+
+#[tokio::test]
+async fn skip() {
+    #[derive(Columns, Extract)]
+    struct X<T> {
+        x: i32,
+        #[column(skip)]
+        cache: Option<String>,
+        #[column(skip, default = 2)]
+        version: i32,
+        _t: PhantomData<T>,
+        y: i32,
+    }
+
+    struct NotFromSql;
+
+    let mut columns = None;
+    let x: X<NotFromSql> = row("select 2 y, 1 x").await.extract(&mut columns);
+    assert_eq!(columns, Some([1, 0]));
+    assert_eq!(x.x, 1);
+    assert_eq!(x.y, 2);
+    assert_eq!(x.cache, None);
+    assert_eq!(x.version, 2);
+}
+
+#[tokio::test]
+async fn transparent() {
+    #[derive(Debug, PartialEq, Columns, Extract)]
+    #[columns(transparent, from_sql, to_sql)]
+    struct UserId(i64);
+
+    #[derive(Columns, Extract)]
+    #[columns(transparent)]
+    struct Name<'a, T> {
+        name: &'a str,
+        _t: PhantomData<T>,
+    }
+
+    #[derive(Columns, Extract)]
+    struct X {
+        id: UserId,
+        owner: Option<UserId>,
+    }
+
+    let id: UserId = row("select 1::int8 a, 2::int8 b").await.extract_once();
+    assert_eq!(id, UserId(1));
+
+    let row = row("select 'a' x").await;
+    let name: Name<String> = row.extract_once();
+    assert_eq!(name.name, "a");
+
+    let row = connect()
+        .await
+        .query_one("select null::int8 owner, $1::int8 id", &[&UserId(3)])
+        .await
+        .unwrap();
+    let x: X = row.extract_once();
+    assert_eq!(x.id, UserId(3));
+    assert_eq!(x.owner, None);
+}
+
+mod domain {
+    pub struct User<T> {
+        pub id: T,
+        pub name: String,
+    }
+
+    pub struct Pair(pub i32, pub i32);
+}
+
+#[tokio::test]
+async fn remote() {
+    #[derive(Columns, Extract)]
+    #[columns(remote = "domain::User<T>")]
+    struct UserDef<T> {
+        id: T,
+        #[column(name = "user")]
+        name: String,
+    }
+
+    #[derive(Columns, Extract)]
+    #[columns(remote = "domain::Pair")]
+    struct PairDef(#[column(name = "a")] i32, #[column(name = "b")] i32);
+
+    let row = row("select 'a' user, 1::int8 id, 2 b, 3 a").await;
+    let user = row.extract_once::<Remote<UserDef<i64>, _>>().into_inner();
+    assert_eq!(user.id, 1);
+    assert_eq!(user.name, "a");
+
+    let pair: domain::Pair = row
+        .try_extract_once::<Remote<PairDef, _>>()
+        .unwrap()
+        .into_inner();
+    assert_eq!((pair.0, pair.1), (3, 2));
+}
+
+#[derive(Columns, Extract)]
+#[columns(by_position(checked))]
+struct Positional {
+    id: i32,
+    #[column(name = "user")]
+    name: String,
+    #[column(skip)]
+    _cache: Option<i32>,
+    #[column(from_columns("a", "b"), with = add)]
+    sum: i32,
+}
+
+#[tokio::test]
+async fn by_position() {
+    let mut columns = None;
+    let x: Positional = row("select 1 id, 'x' user, 2 a, 3 b")
+        .await
+        .extract(&mut columns);
+    assert_eq!(columns, Some([0, 1, 2, 3]));
+    assert_eq!(x.id, 1);
+    assert_eq!(x.name, "x");
+    assert_eq!(x.sum, 5);
+}
+
+#[tokio::test]
+#[should_panic(expected = "`Positional` expects 4 columns but the row has 3 columns")]
+async fn by_position_count() {
+    let _: Positional = row("select 1 id, 'x' user, 2 a").await.extract_once();
+}
+
+#[tokio::test]
+#[should_panic(expected = "column 1 of `Positional` must be named `user` but is named `name`")]
+async fn by_position_name() {
+    let _: Positional = row("select 1 id, 'x' name, 2 a, 3 b").await.extract_once();
+}
+
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[derive(Columns, Extract)]
+struct Aliased {
+    #[column(name = "display_name", alias = "name", alias = "nick")]
+    display_name: String,
+    #[column(alias = "user_id")]
+    id: i32,
+}
+
+#[tokio::test]
+async fn alias() {
+    let mut columns = None;
+    let x: Aliased = row("select 1 user_id, 'a' nick")
+        .await
+        .extract(&mut columns);
+    assert_eq!(columns, Some([1, 0]));
+    assert_eq!(x.display_name, "a");
+    assert_eq!(x.id, 1);
+
+    let mut columns = None;
+    let x: Aliased = row("select 'b' nick, 2 id, 3 user_id, 'a' name")
+        .await
+        .extract(&mut columns);
+    assert_eq!(columns, Some([3, 1]));
+    assert_eq!(x.display_name, "a");
+    assert_eq!(x.id, 2);
+
+    let mut columns = None;
+    let x: Aliased = row("select 'b' nick, 2 user_id, 'a' display_name, 3 id, 'c' name")
+        .await
+        .extract(&mut columns);
+    assert_eq!(columns, Some([2, 3]));
+    assert_eq!(x.display_name, "a");
+    assert_eq!(x.id, 3);
+
+    #[derive(Columns, Extract)]
+    struct X {
+        #[column(alias = "b")]
+        a: i32,
+        #[column(rest)]
+        rest: Vec<(String, i32)>,
+    }
+
+    let x: X = row("select 1 c, 2 b").await.extract_once();
+    assert_eq!(x.a, 2);
+    assert_eq!(x.rest, [("c".to_string(), 1)]);
+}
+
+#[tokio::test]
+#[should_panic(expected = "There is no column named `display_name` or `name` or `nick`")]
+async fn alias_missing() {
+    let _: Aliased = row("select 1 id").await.extract_once();
+}
+
+#[tokio::test]
+async fn case_insensitive() {
+    #[derive(Columns, Extract)]
+    #[columns(case_insensitive)]
+    struct X {
+        user_id: i32,
+        user_nm: String,
+        #[column(name = "Id", alias = "key")]
+        id: i32,
+    }
+
+    let mut columns = None;
+    let x: X = row(r#"select 1 "USER_ID", 'x' "User_Nm", 2 "KEY""#)
+        .await
+        .extract(&mut columns);
+    assert_eq!(columns, Some([0, 1, 2]));
+    assert_eq!(x.user_id, 1);
+    assert_eq!(x.user_nm, "x");
+    assert_eq!(x.id, 2);
+
+    #[derive(Columns, Extract)]
+    #[columns(case_insensitive)]
+    struct Y {
+        #[column(name = "ID")]
+        id: i32,
+        #[column(prefix_vec = "tag_")]
+        tags: Vec<i32>,
+    }
+
+    let y: Y = row(r#"select 3 "TAG_2", 1 id, 2 "Tag_1""#)
+        .await
+        .extract_once();
+    assert_eq!(y.id, 1);
+    assert_eq!(y.tags, [2, 3]);
+}
+
+#[tokio::test]
+async fn rename_all() {
+    #[derive(Columns, Extract)]
+    #[columns(rename_all = "PascalCase")]
+    struct X {
+        user_id: i32,
+        #[column(name = "display_name")]
+        name: String,
+        r#type: i32,
+    }
+
+    let x: X = row(r#"select 1 "UserId", 'a' display_name, 2 "Type""#)
+        .await
+        .extract_once();
+    assert_eq!(x.user_id, 1);
+    assert_eq!(x.name, "a");
+    assert_eq!(x.r#type, 2);
+
+    #[derive(Columns, Extract)]
+    #[columns(rename_all = "SCREAMING-KEBAB-CASE")]
+    struct Y {
+        user_id: i32,
+        #[column(alias = "other")]
+        nick_name: String,
+    }
+
+    let y: Y = row(r#"select 'b' other, 3 "USER-ID""#).await.extract_once();
+    assert_eq!(y.user_id, 3);
+    assert_eq!(y.nick_name, "b");
+}
@@ -0,0 +1,214 @@
+//! Extraction from rows in the text format.
+//!
+//! [`Client::simple_query`](tokio_postgres::Client::simple_query) returns
+//! [`SimpleQueryRow`]s whose values are transmitted as text. Types implementing
+//! [`ExtractText`] can be extracted from such rows and from any other [`TextRow`] such as
+//! the records of a [CSV](crate::csv) file.
+//!
+//! # Examples
+//!
+//! ```
+//! # use tokio_postgres::{Client, Error};
+//! # use tokio_postgres_extractor::{Columns, ExtractText};
+//! # use tokio_postgres_extractor::text::SimpleQueryExtractExt;
+//! #[derive(Columns, ExtractText)]
+//! struct User {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! #[derive(Columns, ExtractText)]
+//! struct Count {
+//!     count: i64,
+//! }
+//!
+//! async fn get_users(client: &Client) -> Result<(Vec<User>, Vec<Count>), Error> {
+//!     let messages = client
+//!         .simple_query("select id, name from users; select count(*) from users")
+//!         .await?;
+//!     let sets = messages.result_sets();
+//!     let users = sets[0].extract().unwrap();
+//!     let count = sets[1].extract().unwrap();
+//!     Ok((users, count))
+//! }
+//! ```
+
+use {
+    crate::{text::sealed::Sealed, Columns, Error},
+    tokio_postgres::{SimpleQueryMessage, SimpleQueryRow},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A row whose values are transmitted as text.
+///
+/// This trait is implemented for [`SimpleQueryRow`], for the records of
+/// [CSV inputs](crate::csv), for [notification payloads](crate::notification), and for
+/// the rows of [logical replication messages](crate::replication).
+pub trait TextRow: Sealed {
+    /// Returns the number of columns.
+    fn num_columns(&self) -> usize;
+
+    /// Returns the name of a column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    fn column_name(&self, idx: usize) -> &str;
+
+    /// Returns the value of a column or `None` if the value is `NULL`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    fn get(&self, idx: usize) -> Option<&str>;
+}
+
+/// A type that can be extracted from a [`TextRow`].
+///
+/// This trait is usually derived with the [`ExtractText`](macro@crate::ExtractText) proc
+/// macro. The derived implementation maps fields to columns by name in the same way as
+/// the [`Columns`](macro@crate::Columns) proc macro and parses each value with
+/// [`FromStr`](std::str::FromStr).
+pub trait ExtractText: Columns + Sized {
+    /// Returns the mapping from the type's fields to the columns in a [`TextRow`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`ExtractText::try_text_columns`] returns an error.
+    fn text_columns(row: &impl TextRow) -> <Self as Columns>::Columns {
+        match Self::try_text_columns(row) {
+            Ok(columns) => columns,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Returns the mapping from the type's fields to the columns in a [`TextRow`].
+    ///
+    /// Returns [`Error::Mapping`] if a field cannot be mapped to a column.
+    fn try_text_columns(row: &impl TextRow) -> Result<<Self as Columns>::Columns, Error>;
+
+    /// Extracts an instance of the type from a [`TextRow`] and a mapping between the
+    /// fields and columns.
+    fn extract_text_with_columns(
+        columns: &<Self as Columns>::Columns,
+        row: &impl TextRow,
+    ) -> Result<Self, Error>;
+
+    /// Extracts an instance of the type from a [`TextRow`].
+    fn extract_text_once(row: &impl TextRow) -> Result<Self, Error> {
+        Self::extract_text(&mut None, row)
+    }
+
+    /// Extracts an instance of the type from a [`TextRow`], memorizing the mapping
+    /// between fields and columns.
+    ///
+    /// The rows must have the same columns in the same order. This is always the case if
+    /// the rows were produced by a single SQL statement or read from a single CSV file.
+    fn extract_text(
+        columns: &mut Option<<Self as Columns>::Columns>,
+        row: &impl TextRow,
+    ) -> Result<Self, Error> {
+        let columns = match columns {
+            Some(columns) => columns,
+            None => columns.insert(Self::try_text_columns(row)?),
+        };
+        Self::extract_text_with_columns(columns, row)
+    }
+}
+
+/// The messages produced by a single statement of a simple query.
+///
+/// Construct it using [`SimpleQueryExtractExt::result_sets`].
+#[derive(Copy, Clone, Debug)]
+pub struct ResultSet<'a> {
+    messages: &'a [SimpleQueryMessage],
+}
+
+impl<'a> ResultSet<'a> {
+    /// Returns an iterator over the rows of the result set.
+    pub fn rows(&self) -> impl Iterator<Item = &'a SimpleQueryRow> {
+        self.messages.iter().filter_map(|m| match m {
+            SimpleQueryMessage::Row(row) => Some(row),
+            _ => None,
+        })
+    }
+
+    /// Returns the number of rows reported by the server, if the statement has completed.
+    pub fn rows_affected(&self) -> Option<u64> {
+        match self.messages.last()? {
+            SimpleQueryMessage::CommandComplete(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Extracts all rows of the result set.
+    pub fn extract<T: ExtractText>(&self) -> Result<Vec<T>, Error> {
+        let mut columns = None;
+        self.rows()
+            .map(|row| T::extract_text(&mut columns, row))
+            .collect()
+    }
+}
+
+/// Extension trait for extracting from the messages returned by
+/// [`Client::simple_query`](tokio_postgres::Client::simple_query).
+pub trait SimpleQueryExtractExt: Sealed {
+    /// Splits the messages into one result set per statement.
+    ///
+    /// Statements that do not return rows produce empty result sets.
+    fn result_sets(&self) -> Vec<ResultSet<'_>>;
+
+    /// Extracts all rows of all result sets.
+    ///
+    /// The outer vector contains one element per statement.
+    fn extract_text<T: ExtractText>(&self) -> Result<Vec<Vec<T>>, Error>;
+}
+
+impl Sealed for SimpleQueryRow {}
+
+impl TextRow for SimpleQueryRow {
+    fn num_columns(&self) -> usize {
+        self.columns().len()
+    }
+
+    fn column_name(&self, idx: usize) -> &str {
+        self.columns()[idx].name()
+    }
+
+    fn get(&self, idx: usize) -> Option<&str> {
+        SimpleQueryRow::get(self, idx)
+    }
+}
+
+impl Sealed for [SimpleQueryMessage] {}
+
+impl SimpleQueryExtractExt for [SimpleQueryMessage] {
+    fn result_sets(&self) -> Vec<ResultSet<'_>> {
+        let mut res = vec![];
+        let mut start = 0;
+        for (idx, message) in self.iter().enumerate() {
+            if let SimpleQueryMessage::CommandComplete(_) = message {
+                res.push(ResultSet {
+                    messages: &self[start..=idx],
+                });
+                start = idx + 1;
+            }
+        }
+        if start < self.len() {
+            res.push(ResultSet {
+                messages: &self[start..],
+            });
+        }
+        res
+    }
+
+    fn extract_text<T: ExtractText>(&self) -> Result<Vec<Vec<T>>, Error> {
+        self.result_sets().iter().map(|set| set.extract()).collect()
+    }
+}
+
+pub(crate) mod sealed {
+    pub trait Sealed {}
+}
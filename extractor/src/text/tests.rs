@@ -0,0 +1,150 @@
+use {
+    crate::{
+        tests::connect,
+        text::{ExtractText, SimpleQueryExtractExt},
+        Error,
+    },
+    std::{collections::HashMap, num::ParseIntError},
+    tokio_postgres_extractor_macros::{Columns, ExtractText},
+};
+
+#[derive(Debug, PartialEq, Columns, ExtractText)]
+struct User {
+    id: i32,
+    name: Option<String>,
+    #[column(null_default = -1)]
+    age: i64,
+    #[column(parse_with = parse_flag)]
+    admin: bool,
+}
+
+fn parse_flag(s: &str) -> Result<bool, String> {
+    match s {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(format!("invalid flag {s}")),
+    }
+}
+
+#[tokio::test]
+async fn extract_text() {
+    let messages = connect()
+        .await
+        .simple_query(
+            "select 'no' admin, null age, 'a' name, 1 id \
+             union all select 'yes', 3, null, 2; \
+             select 1; \
+             select 4 c, 5 c_1, 6 c_0",
+        )
+        .await
+        .unwrap();
+    let sets = messages.result_sets();
+    assert_eq!(sets.len(), 3);
+    assert_eq!(sets[0].rows_affected(), Some(2));
+    let users: Vec<User> = sets[0].extract().unwrap();
+    assert_eq!(
+        users,
+        [
+            User {
+                id: 1,
+                name: Some("a".to_string()),
+                age: -1,
+                admin: false,
+            },
+            User {
+                id: 2,
+                name: None,
+                age: 3,
+                admin: true,
+            },
+        ]
+    );
+
+    #[derive(Columns, ExtractText)]
+    struct X {
+        #[column(prefix_vec = "c_")]
+        c: Vec<u8>,
+        #[column(rest)]
+        rest: HashMap<String, i32>,
+    }
+
+    let x: X = X::extract_text_once(sets[2].rows().next().unwrap()).unwrap();
+    assert_eq!(x.c, [6, 5]);
+    assert_eq!(x.rest, HashMap::from([("c".to_string(), 4)]));
+}
+
+#[tokio::test]
+async fn extract_text_error() {
+    #[derive(Debug, Columns, ExtractText)]
+    struct X {
+        #[allow(dead_code)]
+        a: i32,
+    }
+
+    let messages = connect()
+        .await
+        .simple_query("select 'x' a; select null a; select 1 a")
+        .await
+        .unwrap();
+    let sets = messages.result_sets();
+    match sets[0].extract::<X>().unwrap_err() {
+        Error::Conversion { column, error } => {
+            assert_eq!(column, 0);
+            assert!(error.downcast_ref::<ParseIntError>().is_some());
+        }
+        e => panic!("{e}"),
+    }
+    assert!(matches!(
+        sets[1].extract::<X>().unwrap_err(),
+        Error::Null { column: 0 }
+    ));
+    assert!(messages.extract_text::<X>().is_err());
+    assert_eq!(messages[4..].extract_text::<X>().unwrap()[0][0].a, 1);
+}
+
+#[tokio::test]
+async fn missing_column() {
+    #[derive(Debug, Columns, ExtractText)]
+    #[columns(by_position(checked))]
+    struct Positional {
+        #[allow(dead_code)]
+        a: i32,
+    }
+
+    #[derive(Debug, Columns, ExtractText)]
+    struct Dynamic {
+        #[allow(dead_code)]
+        a: i32,
+        #[allow(dead_code)]
+        #[column(rest)]
+        rest: HashMap<String, i32>,
+    }
+
+    let messages = connect()
+        .await
+        .simple_query("select 1 b, 2 c")
+        .await
+        .unwrap();
+    let row = messages.result_sets()[0].rows().next().unwrap();
+    let message = |res: Result<_, Error>| match res {
+        Err(Error::Mapping { message }) => message,
+        Err(e) => panic!("{e}"),
+        Ok(_) => panic!("mapping succeeded"),
+    };
+    assert_eq!(
+        message(User::try_text_columns(row).map(drop)),
+        "There is no column named `id`",
+    );
+    assert_eq!(
+        message(Dynamic::try_text_columns(row).map(drop)),
+        "There is no column named `a`",
+    );
+    assert_eq!(
+        message(Positional::try_text_columns(row).map(drop)),
+        "`Positional` expects 1 columns but the row has 2 columns",
+    );
+    assert!(matches!(
+        User::extract_text_once(row),
+        Err(Error::Mapping { .. })
+    ));
+}
@@ -0,0 +1,128 @@
+//! Bulk inserts with `UNNEST`.
+//!
+//! For batches that are too small for `COPY` but too large for one statement per row, all
+//! rows can be inserted with a single statement that receives one array per column:
+//!
+//! ```sql
+//! INSERT INTO users ("id", "name") SELECT * FROM UNNEST($1::"pg_catalog"."int4"[], $2::"pg_catalog"."text"[])
+//! ```
+//!
+//! The columns and their types are taken from the [`Unnest`] implementation of the type.
+//!
+//! # Examples
+//!
+//! ```
+//! # use tokio_postgres::{Client, Error};
+//! # use tokio_postgres_extractor::Unnest;
+//! # use tokio_postgres_extractor::unnest::{unnest_insert_sql, UnnestParams};
+//! #[derive(Unnest)]
+//! struct User {
+//!     id: i32,
+//!     name: String,
+//! }
+//!
+//! async fn insert_users(client: &Client, users: &[User]) -> Result<u64, Error> {
+//!     let params = UnnestParams::new(users);
+//!     client
+//!         .execute(&unnest_insert_sql::<User>("users"), &params.params())
+//!         .await
+//! }
+//! ```
+
+use {
+    crate::copy::quote_ident,
+    std::fmt::{self, Debug, Formatter, Write},
+    tokio_postgres::types::{ToSql, Type},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A type whose rows can be inserted with one array per column.
+///
+/// This trait is usually derived with the [`Unnest`](macro@crate::Unnest) proc macro.
+pub trait Unnest {
+    /// The names of the columns, in the order of the arrays.
+    const COLUMN_NAMES: &'static [&'static str];
+
+    /// Returns the types of the elements of the arrays.
+    fn column_types() -> Vec<Type>;
+
+    /// Returns one array per column containing the values of the column in all rows.
+    fn column_arrays(rows: &[Self]) -> Vec<Box<dyn ToSql + Sync + '_>>
+    where
+        Self: Sized;
+}
+
+/// Returns an `INSERT ... SELECT * FROM UNNEST(...)` statement for the table.
+///
+/// `table` is inserted verbatim and can therefore contain a schema. Each parameter is cast
+/// to the array type of its column. The parameters are returned by [`UnnestParams`].
+///
+/// # Examples
+///
+/// ```
+/// # use tokio_postgres_extractor::Unnest;
+/// # use tokio_postgres_extractor::unnest::unnest_insert_sql;
+/// #[derive(Unnest)]
+/// struct User {
+///     id: i32,
+///     name: Option<String>,
+/// }
+///
+/// assert_eq!(
+///     unnest_insert_sql::<User>("users"),
+///     r#"INSERT INTO users ("id", "name") SELECT * FROM UNNEST($1::"pg_catalog"."int4"[], $2::"pg_catalog"."text"[])"#,
+/// );
+/// ```
+pub fn unnest_insert_sql<T: Unnest>(table: &str) -> String {
+    let columns: Vec<_> = T::COLUMN_NAMES.iter().map(|c| quote_ident(c)).collect();
+    let mut res = format!(
+        "INSERT INTO {} ({}) SELECT * FROM UNNEST(",
+        table,
+        columns.join(", "),
+    );
+    for (idx, ty) in T::column_types().iter().enumerate() {
+        if idx > 0 {
+            res.push_str(", ");
+        }
+        let _ = write!(
+            res,
+            "${}::{}.{}[]",
+            idx + 1,
+            quote_ident(ty.schema()),
+            quote_ident(ty.name()),
+        );
+    }
+    res.push(')');
+    res
+}
+
+/// The parameters of the statement returned by [`unnest_insert_sql`].
+///
+/// The rows are transposed into one array per column. The arrays borrow the values of the
+/// rows.
+pub struct UnnestParams<'a> {
+    arrays: Vec<Box<dyn ToSql + Sync + 'a>>,
+}
+
+impl<'a> UnnestParams<'a> {
+    /// Transposes the rows.
+    pub fn new<T: Unnest>(rows: &'a [T]) -> Self {
+        Self {
+            arrays: T::column_arrays(rows),
+        }
+    }
+
+    /// Returns the parameters in the form expected by
+    /// [`Client::execute`](tokio_postgres::Client::execute).
+    pub fn params(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.arrays.iter().map(|a| &**a as _).collect()
+    }
+}
+
+impl Debug for UnnestParams<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.arrays).finish()
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{
+    tests::connect,
+    unnest::{unnest_insert_sql, UnnestParams},
+    Columns, Extract, Unnest,
+};
+
+#[tokio::test]
+async fn unnest() {
+    #[derive(Debug, PartialEq, Columns, Extract, Unnest)]
+    struct Item {
+        id: i32,
+        #[column(name = "Name")]
+        name: Option<String>,
+        #[column(pattern = "q{}", range = 1..=2)]
+        q: [f64; 2],
+        #[column(skip)]
+        skipped: bool,
+    }
+
+    let client = connect().await;
+    client
+        .batch_execute(
+            r#"create temporary table items (id int4, "Name" text, q1 float8, q2 float8, extra text)"#,
+        )
+        .await
+        .unwrap();
+    let items: Vec<_> = (0..100)
+        .map(|id| Item {
+            id,
+            name: (id % 3 != 0).then(|| format!("n{}", id)),
+            q: [id as f64 / 2.0, -1.0],
+            skipped: false,
+        })
+        .collect();
+    let params = UnnestParams::new(&items);
+    let inserted = client
+        .execute(&unnest_insert_sql::<Item>("items"), &params.params())
+        .await
+        .unwrap();
+    assert_eq!(inserted, 100);
+    let rows = client
+        .query(r#"select id, "Name", q1, q2 from items order by id"#, &[])
+        .await
+        .unwrap();
+    let extracted: Vec<Item> = rows.iter().map(Item::extract_once).collect();
+    assert_eq!(extracted, items);
+}
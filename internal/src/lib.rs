@@ -0,0 +1,110 @@
+//! Internal helpers shared by `tokio-postgres-extractor` and
+//! `tokio-postgres-extractor-macros`.
+//!
+//! This crate is not part of the public API and may change at any time.
+
+#[cfg(test)]
+mod tests;
+
+/// Replaces the named parameters in `sql` with positional parameters.
+///
+/// Returns the statement and the names in the order of their positions.
+pub fn rewrite_named_params(sql: &str) -> (String, Vec<&str>) {
+    let bytes = sql.as_bytes();
+    let ident_start = |b: u8| b.is_ascii_alphabetic() || b == b'_' || b >= 0x80;
+    let ident_char = |b: u8| ident_start(b) || b.is_ascii_digit() || b == b'$';
+    let mut res = String::with_capacity(sql.len());
+    let mut names = vec![];
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                let escapes = quote == b'\''
+                    && i > 0
+                    && matches!(bytes[i - 1], b'e' | b'E')
+                    && (i == 1 || !ident_char(bytes[i - 2]));
+                i += 1;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' if escapes => i += 2,
+                        b if b == quote && bytes.get(i + 1) == Some(&quote) => i += 2,
+                        b if b == quote => break,
+                        _ => i += 1,
+                    }
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let mut depth = 0usize;
+                while i < bytes.len() {
+                    match (bytes[i], bytes.get(i + 1)) {
+                        (b'/', Some(b'*')) => {
+                            depth += 1;
+                            i += 2;
+                        }
+                        (b'*', Some(b'/')) => {
+                            depth -= 1;
+                            i += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => i += 1,
+                    }
+                }
+            }
+            b'$' if i == 0 || !ident_char(bytes[i - 1]) => {
+                let mut end = i + 1;
+                if bytes.get(end).is_some_and(|&b| ident_start(b)) {
+                    while bytes.get(end).is_some_and(|&b| b != b'$' && ident_char(b)) {
+                        end += 1;
+                    }
+                }
+                if bytes.get(end) != Some(&b'$') {
+                    i += 1;
+                    continue;
+                }
+                let tag = &sql[i..=end];
+                i = match sql[end + 1..].find(tag) {
+                    Some(pos) => end + 1 + pos + tag.len(),
+                    None => bytes.len(),
+                };
+            }
+            b':' if bytes.get(i + 1) == Some(&b':') => i += 2,
+            b':' if bytes
+                .get(i + 1)
+                .is_some_and(|&b| b.is_ascii_alphabetic() || b == b'_') =>
+            {
+                let mut end = i + 1;
+                while bytes
+                    .get(end)
+                    .is_some_and(|&b| b.is_ascii_alphanumeric() || b == b'_')
+                {
+                    end += 1;
+                }
+                let name = &sql[i + 1..end];
+                let pos = match names.iter().position(|&n| n == name) {
+                    Some(pos) => pos,
+                    None => {
+                        names.push(name);
+                        names.len() - 1
+                    }
+                };
+                res.push_str(&sql[copied..i]);
+                res.push('$');
+                res.push_str(&(pos + 1).to_string());
+                copied = end;
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    res.push_str(&sql[copied..]);
+    (res, names)
+}
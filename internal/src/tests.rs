@@ -0,0 +1,39 @@
+use crate::rewrite_named_params;
+
+#[test]
+fn rewrite_statements() {
+    let cases = [
+        ("select :a, :b, :a", "select $1, $2, $1", &["a", "b"][..]),
+        (
+            "select :a::int4, x::text",
+            "select $1::int4, x::text",
+            &["a"],
+        ),
+        ("select ':a', 'it''s :a'", "select ':a', 'it''s :a'", &[]),
+        (r"select E'\' :a', :b", r"select E'\' :a', $1", &["b"]),
+        (r"select e'\\', :b", r"select e'\\', $1", &["b"]),
+        (r"select '\', :b", r"select '\', $1", &["b"]),
+        (r#"select ":a"":b", :c"#, r#"select ":a"":b", $1"#, &["c"]),
+        ("select 1 -- :a\n, :b", "select 1 -- :a\n, $1", &["b"]),
+        (
+            "select /* /* :a */ :b */ :c",
+            "select /* /* :a */ :b */ $1",
+            &["c"],
+        ),
+        ("select $$ :a $$, :b", "select $$ :a $$, $1", &["b"]),
+        (
+            "select $x$ :a $$ $x$, :b",
+            "select $x$ :a $$ $x$, $1",
+            &["b"],
+        ),
+        ("select a$b, :c", "select a$b, $1", &["c"]),
+        ("select 'ü', :ä", "select 'ü', :ä", &[]),
+        ("select a[1:2], :x", "select a[1:2], $1", &["x"]),
+        ("select 'unterminated :a", "select 'unterminated :a", &[]),
+    ];
+    for (sql, expected, names) in cases {
+        let (res, res_names) = rewrite_named_params(sql);
+        assert_eq!(res, expected, "{}", sql);
+        assert_eq!(res_names, names, "{}", sql);
+    }
+}
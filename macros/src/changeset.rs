@@ -0,0 +1,194 @@
+use {
+    crate::column::{generic_args, get_columns_attr, get_fields, named_columns, set_once, Decode},
+    proc_macro2::TokenStream,
+    quote::quote,
+    std::collections::HashSet,
+    syn::{
+        parse_quote_spanned, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Index,
+        Member, Meta, Path, Type, WherePredicate,
+    },
+};
+
+pub fn changeset_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`Changeset` can only be derived for structs",
+            ))
+        }
+    };
+    let changeset_attr = get_changeset_attr(&input.attrs)?;
+    let rename_all = get_columns_attr(&input.attrs)?.rename_all;
+    let raw_fields: Vec<_> = str.fields.iter().collect();
+    let fields = match &str.fields {
+        Fields::Named(n) => get_fields(&n.named, rename_all)?,
+        Fields::Unnamed(u) => get_fields(&u.unnamed, rename_all)?,
+        Fields::Unit => vec![],
+    };
+    let mut key_names = vec![];
+    let mut key_values = vec![];
+    let mut changes = vec![];
+    let mut diffs = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    let mut unique_types = HashSet::new();
+    let mut to_sql = |ty: &Type, predicates: &mut Vec<WherePredicate>| {
+        if unique_types.insert(ty.clone()) {
+            predicates.push(parse_quote_spanned!(ty.span() =>
+                #ty: ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync
+            ));
+        }
+    };
+    for (idx, (raw, field)) in raw_fields.iter().zip(&fields).enumerate() {
+        let unsupported = match &field.decode {
+            Decode::With(_) => Some("with"),
+            Decode::WithRow(_) => Some("with_row"),
+            Decode::Via(_) => Some("via"),
+            _ if !field.aliases.is_empty() => Some("alias"),
+            _ if field.parse_with.is_some() => Some("parse_with"),
+            _ if field.null_default.is_some() => Some("null_default"),
+            _ if field.sql_type.is_some() => Some("sql_type"),
+            _ if field.sort_key => Some("sort_key"),
+            _ => None,
+        };
+        if let Some(attr) = unsupported {
+            let msg = format!("`Changeset` does not support `{}`", attr);
+            return Err(Error::new_spanned(raw, msg));
+        }
+        let mut names = vec![];
+        named_columns("Changeset", raw, field, &mut names)?;
+        let ty = &raw.ty;
+        let member = match &raw.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        };
+        match &field.decode {
+            Decode::Get => {}
+            Decode::Skip(default) => {
+                let default = match default {
+                    Some(default) => quote!(#default),
+                    None => quote!(::std::default::Default::default()),
+                };
+                diffs.push(quote!(#member: #default));
+                continue;
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    raw,
+                    "`Changeset` only supports fields that map to a single column",
+                ))
+            }
+        }
+        let name = names[0];
+        if field.key || field.primary_key {
+            to_sql(ty, &mut predicates);
+            key_names.push(name);
+            key_values.push(quote!(&self.#member));
+            diffs.push(quote!(#member: ::std::clone::Clone::clone(&old.#member)));
+            continue;
+        }
+        let inner = match generic_args(ty, "Option") {
+            Some(args) if args.len() == 1 => args[0],
+            _ => {
+                return Err(Error::new_spanned(
+                    ty,
+                    "`Changeset` fields must have type `Option<T>` or be annotated with `key` or `primary_key`",
+                ))
+            }
+        };
+        to_sql(inner, &mut predicates);
+        changes.push(quote! {
+            if let ::std::option::Option::Some(value) = &self.#member {
+                changes.push((#name, value as &(dyn ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync)));
+            }
+        });
+        diffs.push(quote! {
+            #member: match old.#member != new.#member {
+                true => ::std::option::Option::Some(::std::clone::Clone::clone(&new.#member)),
+                false => ::std::option::Option::None,
+            }
+        });
+    }
+    if key_names.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`Changeset` requires at least one field annotated with `key` or `primary_key`",
+        ));
+    }
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let diff = changeset_attr.diff.map(|diff| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics ::tokio_postgres_extractor::Diff<#diff> for #name #type_generics #where_clause {
+                fn diff(old: &#diff, new: &#diff) -> Self {
+                    Self { #(#diffs,)* }
+                }
+            }
+        }
+    });
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::Changeset for #name #type_generics #where_clause {
+            const KEY_COLUMNS: &'static [&'static str] = &[#(#key_names,)*];
+
+            fn changes(
+                &self,
+            ) -> ::std::vec::Vec<(&'static str, &(dyn ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync))> {
+                #[allow(unused_mut)]
+                let mut changes = ::std::vec::Vec::new();
+                #(#changes)*
+                changes
+            }
+
+            fn keys(
+                &self,
+            ) -> ::std::vec::Vec<&(dyn ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync)> {
+                ::std::vec![#(#key_values,)*]
+            }
+        }
+
+        #diff
+    })
+}
+
+const CHANGESET_ATTR: &str = "changeset";
+
+#[derive(Default)]
+struct ChangesetAttr {
+    diff: Option<Path>,
+}
+
+fn get_changeset_attr(attrs: &[Attribute]) -> Result<ChangesetAttr, Error> {
+    let mut cattr = ChangesetAttr::default();
+    for attr in attrs {
+        match &attr.meta {
+            Meta::Path(p) => assert_not_changeset_attr(p)?,
+            Meta::NameValue(n) => assert_not_changeset_attr(&n.path)?,
+            Meta::List(l) if l.path.is_ident(CHANGESET_ATTR) => {
+                l.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("diff") {
+                        let diff = meta.value()?.parse()?;
+                        set_once(&mut cattr.diff, &meta.path, "diff", diff)
+                    } else {
+                        Err(meta.error("Unknown attribute"))
+                    }
+                })?;
+            }
+            Meta::List(_) => {}
+        }
+    }
+    Ok(cattr)
+}
+
+fn assert_not_changeset_attr(path: &Path) -> Result<(), Error> {
+    if path.is_ident(CHANGESET_ATTR) {
+        let msg =
+            format!("`{CHANGESET_ATTR}` attribute must be a list attribute: `{CHANGESET_ATTR}()`");
+        return Err(Error::new_spanned(path, msg));
+    }
+    Ok(())
+}
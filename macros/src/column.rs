@@ -1,15 +1,75 @@
 use {
-    proc_macro2::{Literal, Span, TokenStream},
+    crate::extract::fresh_lifetime,
+    proc_macro2::{Ident, Literal, Span, TokenStream},
     quote::quote,
     std::collections::{hash_map::Entry, HashMap, HashSet},
     syn::{
-        parse::Parser, parse_quote_spanned, punctuated::Punctuated, spanned::Spanned, Attribute,
-        Data, DeriveInput, Error, Expr, Field, Fields, Lit, LitStr, Meta, Path, Token,
+        parenthesized, parse::Parse, parse_quote, parse_quote_spanned, punctuated::Punctuated,
+        spanned::Spanned, token, Attribute, Data, DeriveInput, Error, Expr, Field, Fields,
+        GenericArgument, GenericParam, Index, LifetimeParam, Lit, LitInt, LitStr, Member, Meta,
+        Path, PathArguments, Token, Type, WherePredicate,
     },
 };
 
 pub fn columns_impl(input: DeriveInput) -> Result<TokenStream, Error> {
-    let str = match input.data {
+    let Mapping {
+        body,
+        columns_ty,
+        sql_impls,
+    } = mapping(&input)?;
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::Columns for #name #type_generics #where_clause {
+            type Columns = #columns_ty;
+
+            fn try_columns(
+                row: &::tokio_postgres_extractor::private::tokio_postgres::Row,
+            ) -> ::std::result::Result<Self::Columns, ::tokio_postgres_extractor::Error> {
+                #body
+            }
+        }
+
+        #sql_impls
+    })
+}
+
+pub struct Mapping {
+    /// Computes the mapping from the columns of `row` and evaluates to
+    /// `Result<Self::Columns, Error>`.
+    ///
+    /// `row` can be a `Row` or a `SimpleQueryRow`.
+    pub body: TokenStream,
+    pub columns_ty: TokenStream,
+    pub sql_impls: TokenStream,
+}
+
+/// Wraps the successfully computed mapping.
+fn ok(columns: TokenStream) -> TokenStream {
+    quote!(::std::result::Result::Ok(#columns))
+}
+
+/// Returns `Error::Mapping` with the static message `msg`.
+fn fail(msg: TokenStream) -> TokenStream {
+    quote! {
+        return ::std::result::Result::Err(::tokio_postgres_extractor::Error::Mapping {
+            message: ::std::string::ToString::to_string(#msg),
+        })
+    }
+}
+
+/// Returns `Error::Mapping` with a message constructed from the `format!` arguments `args`.
+fn fail_fmt(args: TokenStream) -> TokenStream {
+    quote! {
+        return ::std::result::Result::Err(::tokio_postgres_extractor::Error::Mapping {
+            message: ::std::format!(#args),
+        })
+    }
+}
+
+pub fn mapping(input: &DeriveInput) -> Result<Mapping, Error> {
+    let str = match &input.data {
         Data::Struct(s) => s,
         _ => {
             return Err(Error::new_spanned(
@@ -18,105 +78,124 @@ pub fn columns_impl(input: DeriveInput) -> Result<TokenStream, Error> {
             ))
         }
     };
-    let fields = match str.fields {
-        Fields::Named(n) => get_fields(&n.named)?,
-        Fields::Unnamed(u) => get_fields(&u.unnamed)?,
-        Fields::Unit => vec![],
+    let columns_attr = get_columns_attr(&input.attrs)?;
+    let rename_all = columns_attr.rename_all;
+    let (fields, raw_fields) = match &str.fields {
+        Fields::Named(n) => (get_fields(&n.named, rename_all)?, n.named.iter().collect()),
+        Fields::Unnamed(u) => (
+            get_fields(&u.unnamed, rename_all)?,
+            u.unnamed.iter().collect(),
+        ),
+        Fields::Unit => (vec![], vec![]),
+    };
+    let sql_impls = match columns_attr.transparent {
+        true => transparent_impls(input, &columns_attr, &raw_fields, &fields)?,
+        false => quote!(),
     };
-    let name = input.ident;
-    let num_fields = fields.len();
-    let num_unique_names = fields
+    let name = &input.ident;
+    let columns: Vec<_> = fields.iter().flat_map(|f| &f.columns).collect();
+    let num_fields = columns.len();
+    let mut aliases = vec![];
+    let mut slot = 0;
+    for field in &fields {
+        aliases.extend(field.aliases.iter().map(|alias| (slot, alias)));
+        slot += field.columns.len();
+    }
+    let case_insensitive = columns_attr.case_insensitive;
+    if !columns_attr.transparent && columns_attr.by_position.is_none() {
+        check_name_collisions(&columns, &aliases, case_insensitive)?;
+    }
+    let num_unique_names = columns
         .iter()
-        .filter_map(|f| match &f.column {
-            ColumnIdentifier::Name(n) => Some(n.value()),
+        .filter_map(|c| match c {
+            ColumnIdentifier::Name(n) => Some(n),
             _ => None,
         })
+        .chain(aliases.iter().map(|a| a.1))
+        .map(|n| fold(n, case_insensitive))
         .collect::<HashSet<_>>()
         .len();
-    let body = if num_unique_names == 0 {
+    let dynamic: Vec<_> = fields.iter().filter_map(|f| f.dynamic.as_ref()).collect();
+    let num_dynamic = dynamic.len();
+    let body = if columns_attr.transparent {
+        ok(quote!([0]))
+    } else if let Some(by_position) = &columns_attr.by_position {
+        if !matches!(str.fields, Fields::Named(_)) {
+            return Err(Error::new_spanned(
+                &by_position.path,
+                "`by_position` can only be used with structs with named fields",
+            ));
+        }
+        if num_dynamic > 0 {
+            return Err(Error::new_spanned(
+                &by_position.path,
+                "`by_position` cannot be combined with fields that map to a variable number of columns",
+            ));
+        }
+        generate_positional_body(
+            name,
+            &columns,
+            &aliases,
+            case_insensitive,
+            by_position.checked,
+        )?
+    } else if num_dynamic > 0 {
+        generate_dynamic_body(
+            &columns,
+            &aliases,
+            &dynamic,
+            num_unique_names,
+            case_insensitive,
+        )
+    } else if num_unique_names == 0 {
         let mut ret = vec![];
-        for field in fields.iter() {
-            if let ColumnIdentifier::Index(idx) = &field.column {
+        for column in &columns {
+            if let ColumnIdentifier::Index(idx) = column {
                 ret.push(quote!(#idx));
             }
         }
-        quote! {
-            [#(#ret,)*]
-        }
+        ok(quote!([#(#ret,)*]))
     } else if num_unique_names == 1 {
         let mut ret = vec![];
-        for field in fields.iter() {
-            match &field.column {
+        for column in &columns {
+            match column {
                 ColumnIdentifier::Index(idx) => ret.push(quote!(#idx)),
                 ColumnIdentifier::Name(_) => ret.push(quote!(column_idx)),
             }
         }
-        let name = fields
+        let name = columns
             .iter()
-            .filter_map(|f| match &f.column {
+            .filter_map(|c| match c {
                 ColumnIdentifier::Index(_) => None,
                 ColumnIdentifier::Name(n) => Some(n),
             })
             .next()
             .unwrap();
         let error = format!("There is no column named `{}`", name.value());
+        let matches = name_eq(quote!(column.name()), name, case_insensitive);
+        let found = ok(quote!([#(#ret,)*]));
+        let missing = fail(quote!(#error));
         quote! {
             for (column_idx, column) in row.columns().iter().enumerate() {
-                if column.name() == #name {
-                    return [#(#ret,)*];
+                if #matches {
+                    return #found;
                 }
             }
-            ::std::panic::panic_any(#error);
+            #missing;
         }
     } else {
-        let mut names = HashMap::new();
-        let mut init = vec![];
-        let mut missing_body = vec![];
-        let mut repeats = vec![];
-        for (idx, field) in fields.iter().enumerate() {
-            match &field.column {
-                ColumnIdentifier::Index(idx) => {
-                    init.push(quote!(#idx));
-                }
-                ColumnIdentifier::Name(n) => {
-                    init.push(quote!(!0));
-                    let value = n.value();
-                    let entry = names
-                        .entry(value.len())
-                        .or_insert_with(HashMap::new)
-                        .entry(value.clone());
-                    match entry {
-                        Entry::Vacant(e) => {
-                            e.insert((n, idx));
-                            let error = format!("There is no column named `{}`", n.value());
-                            missing_body.push(quote! {
-                                if entries[#idx] == !0 {
-                                    #error
-                                }
-                            });
-                        }
-                        Entry::Occupied(e) => {
-                            let original = e.get().1;
-                            repeats.push(quote! {
-                                columns[#idx] = columns[#original];
-                            })
-                        }
-                    }
-                }
-            }
-        }
-        let mut names: Vec<_> = names.into_iter().collect();
-        names.sort_by_key(|n| n.0);
-        let mut outer_match_body = vec![];
-        for (len, matches) in names {
-            let inner_match = generate_length_group_body(len, &matches);
-            outer_match_body.push(quote! {
-                #len => #inner_match
-            })
-        }
+        let NameMatcher {
+            init,
+            outer_match_body,
+            num_names,
+            is_name,
+            finish,
+            result,
+        } = generate_name_matcher(&columns, &aliases, case_insensitive, &quote!(continue));
+        let result = ok(result);
         quote! {
             let mut columns = [#(#init,)*];
-            let mut todo = #num_unique_names;
+            let mut todo = #num_names;
             for (column_idx, column) in row.columns().iter().enumerate() {
                 let name = column.name();
                 let idx = match name.len() {
@@ -125,49 +204,478 @@ pub fn columns_impl(input: DeriveInput) -> Result<TokenStream, Error> {
                 };
                 if columns[idx] == !0 {
                     columns[idx] = column_idx;
-                    todo -= 1;
-                    if todo == 0 {
-                        break;
+                    if #is_name {
+                        todo -= 1;
+                        if todo == 0 {
+                            break;
+                        }
                     }
                 }
             }
-            #[cold]
-            fn missing(entries: &[usize; #num_fields]) -> ! {
-                let msg = #(
-                    #missing_body
-                ) else * else {
-                    "entered unreachable code"
-                };
-                ::std::panic::panic_any(msg);
+            #finish
+            #result
+        }
+    };
+    let columns_ty = match num_dynamic {
+        0 => quote!([usize; #num_fields]),
+        _ => quote!(::tokio_postgres_extractor::DynamicColumns<#num_fields, #num_dynamic>),
+    };
+    Ok(Mapping {
+        body,
+        columns_ty,
+        sql_impls,
+    })
+}
+
+fn transparent_impls(
+    input: &DeriveInput,
+    attr: &ColumnsAttr,
+    raw_fields: &[&Field],
+    fields: &[ColumnField],
+) -> Result<TokenStream, Error> {
+    let span = attr.span.unwrap();
+    let mut inner = None;
+    let mut values = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    for (field_idx, (raw, field)) in raw_fields.iter().zip(fields).enumerate() {
+        let member = match &raw.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(field_idx)),
+        };
+        let value = match &field.decode {
+            Decode::Skip(Some(default)) => quote!(#default),
+            Decode::Skip(None) => {
+                let ty = &raw.ty;
+                predicates.push(parse_quote!(#ty: ::std::default::Default));
+                quote!(::std::default::Default::default())
             }
-            if todo > 0 {
-                missing(&columns);
+            _ => {
+                if inner.is_some() {
+                    return Err(Error::new(
+                        span,
+                        "`transparent` requires exactly one field that is not skipped",
+                    ));
+                }
+                if field.columns.len() != 1 || field.dynamic.is_some() {
+                    return Err(Error::new_spanned(
+                        raw,
+                        "the field of a `transparent` struct must consist of a single column",
+                    ));
+                }
+                let sql = attr.from_sql.is_some() || attr.to_sql.is_some();
+                if sql && (!matches!(field.decode, Decode::Get) || field.null_default.is_some()) {
+                    return Err(Error::new_spanned(
+                        raw,
+                        "`from_sql` and `to_sql` cannot be combined with column attributes",
+                    ));
+                }
+                inner = Some((&raw.ty, member.clone()));
+                quote!(value)
+            }
+        };
+        values.push(quote!(#member: #value));
+    }
+    let Some((inner, member)) = inner else {
+        return Err(Error::new(
+            span,
+            "`transparent` requires exactly one field that is not skipped",
+        ));
+    };
+    let name = &input.ident;
+    let (_, type_generics, _) = input.generics.split_for_impl();
+    let mut res = quote!();
+    if attr.from_sql.is_some() {
+        let lt = fresh_lifetime(&input.generics, "a");
+        let mut generics = input.generics.clone();
+        generics
+            .params
+            .push(GenericParam::Lifetime(LifetimeParam::new(lt.clone())));
+        let where_clause = generics.make_where_clause();
+        where_clause.predicates.extend(predicates);
+        where_clause.predicates.push(parse_quote! {
+            #inner: ::tokio_postgres_extractor::private::tokio_postgres::types::FromSql<#lt>
+        });
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        res.extend(quote! {
+            #[automatically_derived]
+            impl #impl_generics ::tokio_postgres_extractor::private::tokio_postgres::types::FromSql<#lt> for #name #type_generics #where_clause {
+                fn from_sql(
+                    ty: &::tokio_postgres_extractor::private::tokio_postgres::types::Type,
+                    raw: &#lt [u8],
+                ) -> ::std::result::Result<Self, ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Sync + ::std::marker::Send>> {
+                    <#inner as ::tokio_postgres_extractor::private::tokio_postgres::types::FromSql<#lt>>::from_sql(ty, raw)
+                        .map(|value| Self { #(#values,)* })
+                }
+
+                fn from_sql_null(
+                    ty: &::tokio_postgres_extractor::private::tokio_postgres::types::Type,
+                ) -> ::std::result::Result<Self, ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Sync + ::std::marker::Send>> {
+                    <#inner as ::tokio_postgres_extractor::private::tokio_postgres::types::FromSql<#lt>>::from_sql_null(ty)
+                        .map(|value| Self { #(#values,)* })
+                }
+
+                fn from_sql_nullable(
+                    ty: &::tokio_postgres_extractor::private::tokio_postgres::types::Type,
+                    raw: ::std::option::Option<&#lt [u8]>,
+                ) -> ::std::result::Result<Self, ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Sync + ::std::marker::Send>> {
+                    <#inner as ::tokio_postgres_extractor::private::tokio_postgres::types::FromSql<#lt>>::from_sql_nullable(ty, raw)
+                        .map(|value| Self { #(#values,)* })
+                }
+
+                fn accepts(ty: &::tokio_postgres_extractor::private::tokio_postgres::types::Type) -> bool {
+                    <#inner as ::tokio_postgres_extractor::private::tokio_postgres::types::FromSql<#lt>>::accepts(ty)
+                }
+            }
+        });
+    }
+    if attr.to_sql.is_some() {
+        let mut generics = input.generics.clone();
+        generics.make_where_clause().predicates.push(parse_quote! {
+            #inner: ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql
+        });
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        res.extend(quote! {
+            #[automatically_derived]
+            impl #impl_generics ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql for #name #type_generics #where_clause {
+                fn to_sql(
+                    &self,
+                    ty: &::tokio_postgres_extractor::private::tokio_postgres::types::Type,
+                    out: &mut ::tokio_postgres_extractor::private::bytes::BytesMut,
+                ) -> ::std::result::Result<::tokio_postgres_extractor::private::tokio_postgres::types::IsNull, ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Sync + ::std::marker::Send>> {
+                    <#inner as ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql>::to_sql(&self.#member, ty, out)
+                }
+
+                fn accepts(ty: &::tokio_postgres_extractor::private::tokio_postgres::types::Type) -> bool {
+                    <#inner as ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql>::accepts(ty)
+                }
+
+                fn to_sql_checked(
+                    &self,
+                    ty: &::tokio_postgres_extractor::private::tokio_postgres::types::Type,
+                    out: &mut ::tokio_postgres_extractor::private::bytes::BytesMut,
+                ) -> ::std::result::Result<::tokio_postgres_extractor::private::tokio_postgres::types::IsNull, ::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Sync + ::std::marker::Send>> {
+                    <#inner as ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql>::to_sql_checked(&self.#member, ty, out)
+                }
+            }
+        });
+    }
+    Ok(res)
+}
+
+fn generate_positional_body(
+    name: &Ident,
+    columns: &[&ColumnIdentifier],
+    aliases: &[(usize, &LitStr)],
+    case_insensitive: bool,
+    checked: bool,
+) -> Result<TokenStream, Error> {
+    let num_fields = columns.len();
+    let name = name.to_string();
+    let mut checks = vec![];
+    for (idx, column) in columns.iter().enumerate() {
+        match column {
+            ColumnIdentifier::Index(idx) => {
+                return Err(Error::new_spanned(
+                    idx,
+                    "`idx` cannot be combined with `by_position`",
+                ))
+            }
+            ColumnIdentifier::Name(column) => {
+                let aliases: Vec<_> = aliases.iter().filter(|a| a.0 == idx).map(|a| a.1).collect();
+                let mut expected = format!("`{}`", column.value());
+                for alias in &aliases {
+                    expected.push_str(&format!(" or `{}`", alias.value()));
+                }
+                let matches = std::iter::once(column)
+                    .chain(aliases)
+                    .map(|n| name_eq(quote!(columns[#idx].name()), n, case_insensitive));
+                let mismatch = fail_fmt(quote! {
+                    "column {} of `{}` must be named {} but is named `{}`",
+                    #idx,
+                    #name,
+                    #expected,
+                    columns[#idx].name(),
+                });
+                checks.push(quote! {
+                    if #(!(#matches))&&* {
+                        #mismatch;
+                    }
+                })
             }
-            #(#repeats)*
-            columns
         }
+    }
+    let condition = match checked {
+        true => quote!(true),
+        false => quote!(::std::cfg!(debug_assertions)),
     };
-    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let indices = 0..num_fields;
+    let count_mismatch = fail_fmt(quote! {
+        "`{}` expects {} columns but the row has {} columns",
+        #name,
+        #num_fields,
+        columns.len(),
+    });
+    let result = ok(quote!([#(#indices,)*]));
     Ok(quote! {
-        #[automatically_derived]
-        impl #impl_generics ::tokio_postgres_extractor::Columns for #name #type_generics #where_clause {
-            type Columns = [usize; #num_fields];
-
-            fn columns(row: &::tokio_postgres_extractor::private::tokio_postgres::Row) -> Self::Columns {
-                #body
+        if #condition {
+            let columns = row.columns();
+            if columns.len() != #num_fields {
+                #count_mismatch;
             }
+            #(#checks)*
         }
+        #result
     })
 }
 
+struct NameMatcher {
+    init: Vec<TokenStream>,
+    outer_match_body: Vec<TokenStream>,
+    /// The number of distinct names of the fields, excluding aliases.
+    ///
+    /// Once all of them have been found, the remaining columns cannot change the mapping
+    /// since names take priority over aliases.
+    num_names: usize,
+    /// An expression that is true if the matched slot `idx` belongs to a name instead of
+    /// an alias.
+    is_name: TokenStream,
+    /// Statements that complete the mapping after all columns have been inspected.
+    finish: TokenStream,
+    /// The mapping of the fields.
+    result: TokenStream,
+}
+
+fn generate_name_matcher(
+    columns: &[&ColumnIdentifier],
+    aliases: &[(usize, &LitStr)],
+    case_insensitive: bool,
+    miss: &TokenStream,
+) -> NameMatcher {
+    let num_fields = columns.len();
+    let num_slots = num_fields + aliases.len();
+    let mut names = HashMap::new();
+    let mut init = vec![];
+    let mut missing_body = vec![];
+    let mut alias_repeats = vec![];
+    let mut resolve = vec![];
+    let mut repeats = vec![];
+    let mut num_names = 0;
+    for (idx, column) in columns.iter().enumerate() {
+        match column {
+            ColumnIdentifier::Index(idx) => {
+                init.push(quote!(#idx));
+            }
+            ColumnIdentifier::Name(n) => {
+                init.push(quote!(!0));
+                let value = fold(n, case_insensitive);
+                let entry = names
+                    .entry(value.len())
+                    .or_insert_with(HashMap::new)
+                    .entry(value.clone());
+                match entry {
+                    Entry::Vacant(e) => {
+                        e.insert((n, idx));
+                        num_names += 1;
+                        let mut error = format!("There is no column named `{}`", n.value());
+                        for (_, alias) in aliases.iter().filter(|a| a.0 == idx) {
+                            error.push_str(&format!(" or `{}`", alias.value()));
+                        }
+                        missing_body.push(quote! {
+                            if entries[#idx] == !0 {
+                                #error
+                            }
+                        });
+                    }
+                    Entry::Occupied(e) => {
+                        let original = e.get().1;
+                        repeats.push(quote! {
+                            columns[#idx] = columns[#original];
+                        })
+                    }
+                }
+            }
+        }
+    }
+    for (alias_idx, &(field_idx, alias)) in aliases.iter().enumerate() {
+        let idx = num_fields + alias_idx;
+        init.push(quote!(!0));
+        let value = fold(alias, case_insensitive);
+        let entry = names
+            .entry(value.len())
+            .or_insert_with(HashMap::new)
+            .entry(value.clone());
+        match entry {
+            Entry::Vacant(e) => {
+                e.insert((alias, idx));
+            }
+            Entry::Occupied(e) => {
+                let original = e.get().1;
+                alias_repeats.push(quote! {
+                    columns[#idx] = columns[#original];
+                })
+            }
+        }
+        resolve.push(quote! {
+            if columns[#field_idx] == !0 {
+                columns[#field_idx] = columns[#idx];
+            }
+        });
+    }
+    let mut names: Vec<_> = names.into_iter().collect();
+    names.sort_by_key(|n| n.0);
+    let mut outer_match_body = vec![];
+    for (len, matches) in names {
+        let inner_match = generate_length_group_body(len, &matches, case_insensitive, miss);
+        outer_match_body.push(quote! {
+            #len => #inner_match
+        })
+    }
+    let (is_name, incomplete, result) = match aliases.len() {
+        0 => (quote!(true), quote!(todo > 0), quote!(columns)),
+        _ => {
+            let indices = 0..num_fields;
+            (
+                quote!(idx < #num_fields),
+                quote!(columns[..#num_fields].contains(&!0)),
+                quote!([#(columns[#indices],)*]),
+            )
+        }
+    };
+    let missing = fail(quote!(missing(&columns)));
+    let finish = quote! {
+        #(#alias_repeats)*
+        #(#resolve)*
+        #(#repeats)*
+        #[cold]
+        fn missing(entries: &[usize; #num_slots]) -> &'static str {
+            #(
+                #missing_body
+            ) else * else {
+                "entered unreachable code"
+            }
+        }
+        if #incomplete {
+            #missing;
+        }
+    };
+    NameMatcher {
+        init,
+        outer_match_body,
+        num_names,
+        is_name,
+        finish,
+        result,
+    }
+}
+
+fn generate_dynamic_body(
+    columns: &[&ColumnIdentifier],
+    aliases: &[(usize, &LitStr)],
+    dynamic: &[&Dynamic],
+    num_unique_names: usize,
+    case_insensitive: bool,
+) -> TokenStream {
+    let mut fixed = None;
+    let mut finish = None;
+    let mut result = quote!(columns);
+    let mut init = vec![];
+    let mut num_names = 0;
+    if num_unique_names > 0 {
+        let miss = quote!(break 'fixed);
+        let matcher = generate_name_matcher(columns, aliases, case_insensitive, &miss);
+        let outer_match_body = matcher.outer_match_body;
+        let is_name = matcher.is_name;
+        init = matcher.init;
+        num_names = matcher.num_names;
+        finish = Some(matcher.finish);
+        result = matcher.result;
+        fixed = Some(quote! {
+            'fixed: {
+                let idx = match name.len() {
+                    #(#outer_match_body,)*
+                    _ => #miss,
+                };
+                if columns[idx] == !0 {
+                    columns[idx] = column_idx;
+                    if #is_name {
+                        todo -= 1;
+                    }
+                    continue 'columns;
+                }
+            }
+        });
+    } else {
+        for column in columns {
+            if let ColumnIdentifier::Index(idx) = column {
+                init.push(quote!(#idx));
+            }
+        }
+    }
+    let mut dynamic_init = vec![];
+    let mut dynamic_match = vec![];
+    let mut dynamic_finish = vec![];
+    let mut rest = None;
+    for (idx, dynamic) in dynamic.iter().enumerate() {
+        let var = Ident::new(&format!("dynamic_{idx}"), Span::call_site());
+        dynamic_init.push(quote! {
+            let mut #var = ::std::vec::Vec::new();
+        });
+        match dynamic {
+            Dynamic::Prefix(prefix) => {
+                let numbered_column = match case_insensitive {
+                    true => quote!(numbered_column_ignore_ascii_case),
+                    false => quote!(numbered_column),
+                };
+                dynamic_match.push(quote! {
+                    if let Some(n) = ::tokio_postgres_extractor::private::#numbered_column(name, #prefix) {
+                        #var.push((n, column_idx));
+                        continue 'columns;
+                    }
+                });
+                dynamic_finish.push(quote! {
+                    ::tokio_postgres_extractor::private::sort_numbered(#var)
+                });
+            }
+            Dynamic::Rest => {
+                rest = Some(quote! {
+                    #var.push(column_idx);
+                });
+                dynamic_finish.push(quote!(#var));
+            }
+        }
+    }
+    let result = ok(quote! {
+        ::tokio_postgres_extractor::DynamicColumns {
+            fixed: #result,
+            dynamic: [#(#dynamic_finish,)*],
+        }
+    });
+    quote! {
+        #[allow(unused_mut)]
+        let mut columns = [#(#init,)*];
+        let mut todo = #num_names;
+        #(#dynamic_init)*
+        'columns: for (column_idx, column) in row.columns().iter().enumerate() {
+            let name = column.name();
+            #fixed
+            #(#dynamic_match)*
+            #rest
+        }
+        #finish
+        #result
+    }
+}
+
 fn generate_length_group_body(
     len: usize,
     names: &HashMap<String, (&LitStr, usize)>,
+    case_insensitive: bool,
+    miss: &TokenStream,
 ) -> TokenStream {
     let mut names: Vec<_> = names.iter().map(|v| (v.0, v.1 .0, v.1 .1)).collect();
     names.sort_by_key(|v| v.0);
     if names.len() == 1 || matches!(len, 1 | 2 | 4 | 8) {
-        return generate_fallback_length_group_body(&names);
+        return generate_fallback_length_group_body(&names, case_insensitive, miss);
     }
     let mut unique = HashSet::new();
     for sub_len_shift in 0..4 {
@@ -193,35 +701,42 @@ fn generate_length_group_body(
             let e3 = |b: &[u8]| {
                 u64::from_le_bytes([b[s], b[s1], b[s2], b[s3], b[s4], b[s5], b[s6], b[s7]])
             };
+            let byte = |idx: usize| match case_insensitive {
+                true => quote!(b[#idx].to_ascii_lowercase()),
+                false => quote!(b[#idx]),
+            };
+            let [b0, b1, b2, b3, b4, b5, b6, b7] = [s, s1, s2, s3, s4, s5, s6, s7].map(byte);
             #[allow(clippy::type_complexity)]
             let (extract_rt, extract_ct): (_, &dyn Fn(&[u8]) -> u64) = match sub_len_shift {
                 0 => {
-                    let rt = quote!(b[#start]);
+                    let rt = quote!(#b0);
                     (rt, &e0)
                 }
                 1 => {
-                    let rt = quote!(u16::from_le_bytes([b[#s], b[#s1]]));
+                    let rt = quote!(u16::from_le_bytes([#b0, #b1]));
                     (rt, &e1)
                 }
                 2 => {
-                    let rt = quote!(u32::from_le_bytes([b[#s], b[#s1], b[#s2], b[#s3]]));
+                    let rt = quote!(u32::from_le_bytes([#b0, #b1, #b2, #b3]));
                     (rt, &e2)
                 }
                 3 => {
-                    let rt = quote!(u64::from_le_bytes([b[#s], b[#s1], b[#s2], b[#s3], b[#s4], b[#s5], b[#s6], b[#s7]]));
+                    let rt = quote!(u64::from_le_bytes([#b0, #b1, #b2, #b3, #b4, #b5, #b6, #b7]));
                     (rt, &e3)
                 }
                 _ => unreachable!(),
             };
             let mut disc_match_body = vec![];
-            for (name, lit, idx) in &names {
+            for &(name, lit, idx) in &names {
                 let disc = extract_ct(name.as_bytes());
                 let disc = Literal::u64_unsuffixed(disc);
+                let body = generate_fallback_length_group_body(
+                    &[(name, lit, idx)],
+                    case_insensitive,
+                    miss,
+                );
                 disc_match_body.push(quote! {
-                    #disc => match name {
-                        #lit => #idx,
-                        _ => continue,
-                    }
+                    #disc => #body
                 })
             }
             return quote! {{
@@ -229,15 +744,30 @@ fn generate_length_group_body(
                 let disc = #extract_rt;
                 match disc {
                     #(#disc_match_body,)*
-                    _ => continue,
+                    _ => #miss,
                 }
             }};
         }
     }
-    generate_fallback_length_group_body(&names)
+    generate_fallback_length_group_body(&names, case_insensitive, miss)
 }
 
-fn generate_fallback_length_group_body(names: &[(&String, &LitStr, usize)]) -> TokenStream {
+fn generate_fallback_length_group_body(
+    names: &[(&String, &LitStr, usize)],
+    case_insensitive: bool,
+    miss: &TokenStream,
+) -> TokenStream {
+    if case_insensitive {
+        let conditions = names
+            .iter()
+            .map(|(_, lit, _)| name_eq(quote!(name), lit, true));
+        let indices = names.iter().map(|n| n.2);
+        return quote! {
+            #(if #conditions { #indices } else)* {
+                #miss
+            }
+        };
+    }
     let mut inner_match_body = vec![];
     for (_, name, idx) in names {
         inner_match_body.push(quote! {
@@ -247,51 +777,753 @@ fn generate_fallback_length_group_body(names: &[(&String, &LitStr, usize)]) -> T
     quote! {
         match name {
             #(#inner_match_body,)*
-            _ => continue,
+            _ => #miss,
         }
     }
 }
 
-enum ColumnIdentifier {
+/// Returns the name used to identify the column in the matcher.
+fn fold(name: &LitStr, case_insensitive: bool) -> String {
+    match case_insensitive {
+        true => name.value().to_ascii_lowercase(),
+        false => name.value(),
+    }
+}
+
+/// Rejects names that would silently map different names to the same column.
+///
+/// Fields with identical names share a column. Names that are only equal after folding
+/// and aliases that are equal to the name of a field are errors.
+fn check_name_collisions(
+    columns: &[&ColumnIdentifier],
+    aliases: &[(usize, &LitStr)],
+    case_insensitive: bool,
+) -> Result<(), Error> {
+    let names = columns
+        .iter()
+        .filter_map(|c| match c {
+            ColumnIdentifier::Name(n) => Some((n, false)),
+            _ => None,
+        })
+        .chain(aliases.iter().map(|a| (a.1, true)));
+    let mut seen = HashMap::new();
+    for (name, is_alias) in names {
+        match seen.entry(fold(name, case_insensitive)) {
+            Entry::Vacant(e) => {
+                e.insert((name, is_alias));
+            }
+            Entry::Occupied(e) => {
+                let (other, other_is_alias) = *e.get();
+                let msg = if is_alias && !other_is_alias {
+                    format!(
+                        "alias `{}` collides with the column name `{}`",
+                        name.value(),
+                        other.value(),
+                    )
+                } else if name.value() != other.value() {
+                    format!(
+                        "column name `{}` collides with `{}` under `case_insensitive`",
+                        name.value(),
+                        other.value(),
+                    )
+                } else {
+                    continue;
+                };
+                return Err(Error::new_spanned(name, msg));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns an expression comparing the column name `actual` to `expected`.
+fn name_eq(actual: TokenStream, expected: &LitStr, case_insensitive: bool) -> TokenStream {
+    match case_insensitive {
+        true => quote!(#actual.eq_ignore_ascii_case(#expected)),
+        false => quote!(#actual == #expected),
+    }
+}
+
+pub enum ColumnIdentifier {
     Index(Expr),
     Name(LitStr),
 }
 
-struct ColumnField {
-    column: ColumnIdentifier,
+pub enum Decode {
+    Get,
+    With(Path),
+    /// Calls the function with the row and the index of the column.
+    WithRow(Path),
+    Via(Type),
+    Array(Type),
+    Vec(Type),
+    Rest(Type),
+    Skip(Option<Expr>),
+}
+
+pub enum Dynamic {
+    Prefix(LitStr),
+    Rest,
+}
+
+pub enum NullDefault {
+    Default,
+    Expr(Expr),
 }
 
-fn get_fields(fields: &Punctuated<Field, Token![,]>) -> Result<Vec<ColumnField>, Error> {
+pub struct ColumnField {
+    pub columns: Vec<ColumnIdentifier>,
+    pub dynamic: Option<Dynamic>,
+    pub decode: Decode,
+    pub null_default: Option<NullDefault>,
+    /// Alternative names of the column, in order of decreasing priority.
+    pub aliases: Vec<LitStr>,
+    /// The function used to parse values in the text format.
+    pub parse_with: Option<Path>,
+    /// The Postgres type of the columns.
+    pub sql_type: Option<Expr>,
+    /// Whether the columns are part of the conflict target of upserts.
+    pub key: bool,
+    /// Whether the column is the primary key of the table.
+    pub primary_key: bool,
+    /// Whether the column is part of the ordering used for keyset pagination.
+    pub sort_key: bool,
+}
+
+pub fn get_fields(
+    fields: &Punctuated<Field, Token![,]>,
+    rename_all: Option<RenameAll>,
+) -> Result<Vec<ColumnField>, Error> {
     let mut res = vec![];
+    let mut has_rest = false;
     for (field_idx, field) in fields.iter().enumerate() {
         let attr = get_column_attr(&field.attrs)?;
-        let column = match (attr.idx, attr.name) {
-            (Some(_), Some(_)) => {
+        let phantom = !attr.skip && is_phantom_data(&field.ty);
+        let skip = attr.skip || phantom;
+        if attr.default.is_some() && !skip {
+            return Err(Error::new(attr.span.unwrap(), "`default` requires `skip`"));
+        }
+        if skip
+            && (attr.idx.is_some()
+                || attr.name.is_some()
+                || attr.from_columns.is_some()
+                || attr.with.is_some()
+                || attr.with_row.is_some()
+                || attr.via.is_some()
+                || attr.pattern.is_some()
+                || attr.range.is_some()
+                || attr.prefix_vec.is_some()
+                || attr.rest
+                || attr.null_default.is_some()
+                || !attr.aliases.is_empty()
+                || attr.parse_with.is_some()
+                || attr.sql_type.is_some()
+                || attr.key
+                || attr.primary_key
+                || attr.sort_key)
+        {
+            let msg = match phantom {
+                true => {
+                    "`PhantomData` fields are skipped, `skip` can only be combined with `default`"
+                }
+                false => "`skip` can only be combined with `default`",
+            };
+            return Err(Error::new(attr.span.unwrap(), msg));
+        }
+        if skip {
+            let default = match attr.default {
+                Some(default) => Some(default),
+                None if attr.skip => None,
+                None => Some(parse_quote_spanned!(field.ty.span() => ::std::marker::PhantomData)),
+            };
+            res.push(ColumnField {
+                columns: vec![],
+                dynamic: None,
+                decode: Decode::Skip(default),
+                null_default: None,
+                aliases: vec![],
+                parse_with: None,
+                sql_type: None,
+                key: false,
+                primary_key: false,
+                sort_key: false,
+            });
+            continue;
+        }
+        if attr.range.is_some() && attr.pattern.is_none() {
+            return Err(Error::new(attr.span.unwrap(), "`range` requires `pattern`"));
+        }
+        if attr.pattern.is_some() || attr.prefix_vec.is_some() || attr.rest {
+            let (attr_name, other) = match (attr.pattern.is_some(), attr.prefix_vec.is_some()) {
+                (true, _) => ("pattern", attr.prefix_vec.is_some() || attr.rest),
+                (_, true) => ("prefix_vec", attr.rest),
+                _ => ("rest", false),
+            };
+            if other
+                || attr.idx.is_some()
+                || attr.name.is_some()
+                || attr.from_columns.is_some()
+                || attr.with.is_some()
+                || attr.via.is_some()
+                || attr.null_default.is_some()
+                || !attr.aliases.is_empty()
+            {
+                let msg = format!("`{attr_name}` cannot be combined with other attributes");
+                return Err(Error::new(attr.span.unwrap(), msg));
+            }
+        }
+        if let Some(pattern) = attr.pattern {
+            let Some((start, end)) = attr.range else {
+                return Err(Error::new(attr.span.unwrap(), "`pattern` requires `range`"));
+            };
+            let Type::Array(array) = &field.ty else {
+                return Err(Error::new_spanned(
+                    &field.ty,
+                    "`pattern` requires a field of array type",
+                ));
+            };
+            let value = pattern.value();
+            if !value.contains("{}") {
+                return Err(Error::new_spanned(
+                    pattern,
+                    "`pattern` must contain the placeholder `{}`",
+                ));
+            }
+            let columns = (start..end)
+                .map(|i| {
+                    let name = value.replace("{}", &i.to_string());
+                    ColumnIdentifier::Name(LitStr::new(&name, pattern.span()))
+                })
+                .collect();
+            res.push(ColumnField {
+                columns,
+                dynamic: None,
+                decode: Decode::Array((*array.elem).clone()),
+                null_default: None,
+                aliases: vec![],
+                parse_with: attr.parse_with,
+                sql_type: attr.sql_type,
+                key: attr.key,
+                primary_key: attr.primary_key,
+                sort_key: attr.sort_key,
+            });
+            continue;
+        }
+        if let Some(prefix) = attr.prefix_vec {
+            let Some(elem) = vec_elem(&field.ty) else {
+                return Err(Error::new_spanned(
+                    &field.ty,
+                    "`prefix_vec` requires a field of type `Vec<T>`",
+                ));
+            };
+            res.push(ColumnField {
+                columns: vec![],
+                dynamic: Some(Dynamic::Prefix(prefix)),
+                decode: Decode::Vec(elem.clone()),
+                null_default: None,
+                aliases: vec![],
+                parse_with: attr.parse_with,
+                sql_type: attr.sql_type,
+                key: attr.key,
+                primary_key: attr.primary_key,
+                sort_key: attr.sort_key,
+            });
+            continue;
+        }
+        if attr.rest {
+            if has_rest {
+                return Err(Error::new(
+                    attr.span.unwrap(),
+                    "`rest` can only be specified for a single field",
+                ));
+            }
+            has_rest = true;
+            let Some(value) = rest_value(&field.ty) else {
+                return Err(Error::new_spanned(
+                    &field.ty,
+                    "`rest` requires a field of type `HashMap<String, T>`, \
+                     `BTreeMap<String, T>`, or `Vec<(String, T)>`",
+                ));
+            };
+            res.push(ColumnField {
+                columns: vec![],
+                dynamic: Some(Dynamic::Rest),
+                decode: Decode::Rest(value.clone()),
+                null_default: None,
+                aliases: vec![],
+                parse_with: attr.parse_with,
+                sql_type: attr.sql_type,
+                key: attr.key,
+                primary_key: attr.primary_key,
+                sort_key: attr.sort_key,
+            });
+            continue;
+        }
+        let explicit = attr.idx.is_some() || attr.from_columns.is_some();
+        let columns = match (attr.idx, attr.name, attr.from_columns) {
+            (Some(_), Some(_), _) => {
                 return Err(Error::new(
                     attr.span.unwrap(),
                     "Cannot specify both `idx` and `name`",
                 ))
             }
-            (Some(idx), _) => ColumnIdentifier::Index(idx),
-            (_, Some(rename)) => ColumnIdentifier::Name(rename),
-            _ => match field.ident.clone() {
+            (Some(_), _, Some(_)) | (_, Some(_), Some(_)) => {
+                return Err(Error::new(
+                    attr.span.unwrap(),
+                    "Cannot specify `from_columns` together with `idx` or `name`",
+                ))
+            }
+            (_, _, Some(names)) => {
+                if attr.with.is_none() {
+                    return Err(Error::new(
+                        attr.span.unwrap(),
+                        "`from_columns` requires `with`",
+                    ));
+                }
+                names.into_iter().map(ColumnIdentifier::Name).collect()
+            }
+            (Some(idx), _, _) => vec![ColumnIdentifier::Index(idx)],
+            (_, Some(rename), _) => vec![ColumnIdentifier::Name(rename)],
+            _ => vec![match field.ident.clone() {
                 None => ColumnIdentifier::Index(parse_quote_spanned! {
                     field.span() => #field_idx
                 }),
                 Some(ident) => {
                     let ident_str = ident.to_string();
                     let ident_str = ident_str.strip_prefix("r#").unwrap_or(&ident_str);
-                    ColumnIdentifier::Name(parse_quote_spanned! {
-                        ident.span() => #ident_str
-                    })
+                    let name = match rename_all {
+                        Some(rename_all) => rename_all.apply(ident_str),
+                        None => ident_str.to_string(),
+                    };
+                    ColumnIdentifier::Name(LitStr::new(&name, ident.span()))
+                }
+            }],
+        };
+        if !attr.aliases.is_empty() {
+            let name = match &columns[..] {
+                [ColumnIdentifier::Name(name)] => name.value(),
+                _ if explicit => {
+                    return Err(Error::new(
+                        attr.span.unwrap(),
+                        "`alias` cannot be combined with `idx` or `from_columns`",
+                    ))
+                }
+                _ => return Err(Error::new(attr.span.unwrap(), "`alias` requires `name`")),
+            };
+            let mut names = HashSet::new();
+            names.insert(name);
+            for alias in &attr.aliases {
+                if !names.insert(alias.value()) {
+                    return Err(Error::new_spanned(alias, "Duplicate column name"));
+                }
+            }
+        }
+        let decode = match (attr.with, attr.with_row, attr.via) {
+            (Some(_), Some(_), _) => {
+                return Err(Error::new(
+                    attr.span.unwrap(),
+                    "Cannot specify both `with` and `with_row`",
+                ))
+            }
+            (Some(_), _, Some(_)) => {
+                return Err(Error::new(
+                    attr.span.unwrap(),
+                    "Cannot specify both `with` and `via`",
+                ))
+            }
+            (_, Some(_), Some(_)) => {
+                return Err(Error::new(
+                    attr.span.unwrap(),
+                    "Cannot specify both `with_row` and `via`",
+                ))
+            }
+            (Some(with), _, _) => Decode::With(with),
+            (_, Some(with_row), _) => {
+                if columns.len() != 1 {
+                    return Err(Error::new(
+                        attr.span.unwrap(),
+                        "`with_row` cannot be combined with `from_columns`",
+                    ));
                 }
-            },
+                Decode::WithRow(with_row)
+            }
+            (_, _, Some(via)) => Decode::Via(via),
+            _ => Decode::Get,
         };
-        res.push(ColumnField { column });
+        match &decode {
+            Decode::With(_) if attr.null_default.is_some() => {
+                return Err(Error::new(
+                    attr.span.unwrap(),
+                    "Cannot specify both `with` and `null_default`",
+                ));
+            }
+            Decode::WithRow(_) if attr.null_default.is_some() => {
+                return Err(Error::new(
+                    attr.span.unwrap(),
+                    "Cannot specify both `with_row` and `null_default`",
+                ));
+            }
+            _ => {}
+        }
+        res.push(ColumnField {
+            columns,
+            dynamic: None,
+            decode,
+            null_default: attr.null_default,
+            aliases: attr.aliases,
+            parse_with: attr.parse_with,
+            sql_type: attr.sql_type,
+            key: attr.key,
+            primary_key: attr.primary_key,
+            sort_key: attr.sort_key,
+        });
+    }
+    Ok(res)
+}
+
+pub fn named_columns<'a>(
+    derive: &str,
+    raw: &Field,
+    field: &'a ColumnField,
+    names: &mut Vec<&'a LitStr>,
+) -> Result<(), Error> {
+    if field.dynamic.is_some() {
+        return Err(Error::new_spanned(
+            raw,
+            format!(
+                "`{}` does not support fields that map to a variable number of columns",
+                derive
+            ),
+        ));
+    }
+    for column in &field.columns {
+        match column {
+            ColumnIdentifier::Name(name) => names.push(name),
+            ColumnIdentifier::Index(idx) => {
+                return Err(Error::new_spanned(
+                    idx,
+                    format!(
+                        "`{}` requires column names, use `#[column(name = \"...\")]`",
+                        derive
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A column written by a derived implementation.
+pub struct ToSqlColumn<'a> {
+    pub name: &'a LitStr,
+    /// The type of the value.
+    pub ty: &'a Type,
+    pub field: &'a ColumnField,
+    /// The place of the value relative to the struct, e.g., `name` or `values[0]`.
+    pub place: TokenStream,
+}
+
+/// Returns the columns written by the fields of a struct.
+///
+/// Adds `ToSql + Sync` bounds for the types of the values to `predicates`.
+pub fn to_sql_columns<'a>(
+    derive: &str,
+    raw_fields: &[&'a Field],
+    fields: &'a [ColumnField],
+    predicates: &mut Vec<WherePredicate>,
+) -> Result<Vec<ToSqlColumn<'a>>, Error> {
+    let mut res = vec![];
+    let mut unique_types = HashSet::new();
+    let mut to_sql = |ty: &Type, predicates: &mut Vec<WherePredicate>| {
+        if unique_types.insert(ty.clone()) {
+            predicates.push(parse_quote_spanned!(ty.span() =>
+                #ty: ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync
+            ));
+        }
+    };
+    for (idx, (raw, field)) in raw_fields.iter().zip(fields).enumerate() {
+        let mut names = vec![];
+        named_columns(derive, raw, field, &mut names)?;
+        let member = match &raw.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        };
+        match &field.decode {
+            Decode::Get => {
+                to_sql(&raw.ty, predicates);
+                res.push(ToSqlColumn {
+                    name: names[0],
+                    ty: &raw.ty,
+                    field,
+                    place: quote!(#member),
+                });
+            }
+            Decode::Skip(_) => {}
+            Decode::Array(elem) => {
+                to_sql(elem, predicates);
+                for (i, name) in names.into_iter().enumerate() {
+                    res.push(ToSqlColumn {
+                        name,
+                        ty: elem,
+                        field,
+                        place: quote!(#member[#i]),
+                    });
+                }
+            }
+            Decode::Via(_) => {
+                let msg = format!("`{}` does not support `via`", derive);
+                return Err(Error::new_spanned(raw, msg));
+            }
+            Decode::With(_) => {
+                let msg = format!("`{}` does not support `with`", derive);
+                return Err(Error::new_spanned(raw, msg));
+            }
+            Decode::WithRow(_) => {
+                let msg = format!("`{}` does not support `with_row`", derive);
+                return Err(Error::new_spanned(raw, msg));
+            }
+            Decode::Vec(_) | Decode::Rest(_) => unreachable!(),
+        }
     }
     Ok(res)
 }
 
+pub fn generic_args<'a>(ty: &'a Type, name: &str) -> Option<Vec<&'a Type>> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args
+        .iter()
+        .map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_phantom_data(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    match path.path.segments.last() {
+        Some(segment) => segment.ident == "PhantomData",
+        None => false,
+    }
+}
+
+fn vec_elem(ty: &Type) -> Option<&Type> {
+    match generic_args(ty, "Vec")?[..] {
+        [elem] => Some(elem),
+        _ => None,
+    }
+}
+
+fn rest_value(ty: &Type) -> Option<&Type> {
+    if let Some(args) = generic_args(ty, "HashMap").or_else(|| generic_args(ty, "BTreeMap")) {
+        return match args[..] {
+            [_, value, ..] => Some(value),
+            _ => None,
+        };
+    }
+    match vec_elem(ty)? {
+        Type::Tuple(tuple) if tuple.elems.len() == 2 => tuple.elems.last(),
+        _ => None,
+    }
+}
+
+const COLUMNS_ATTR: &str = "columns";
+
+#[derive(Default)]
+pub struct ColumnsAttr {
+    pub span: Option<Span>,
+    pub transparent: bool,
+    pub from_sql: Option<Path>,
+    pub to_sql: Option<Path>,
+    pub remote: Option<Path>,
+    pub by_position: Option<ByPosition>,
+    pub case_insensitive: bool,
+    pub rename_all: Option<RenameAll>,
+}
+
+/// A convention for deriving column names from field names.
+#[derive(Copy, Clone)]
+pub enum RenameAll {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameAll {
+    const NAMES: &'static [(&'static str, RenameAll)] = &[
+        ("lowercase", RenameAll::Lower),
+        ("UPPERCASE", RenameAll::Upper),
+        ("PascalCase", RenameAll::Pascal),
+        ("camelCase", RenameAll::Camel),
+        ("snake_case", RenameAll::Snake),
+        ("SCREAMING_SNAKE_CASE", RenameAll::ScreamingSnake),
+        ("kebab-case", RenameAll::Kebab),
+        ("SCREAMING-KEBAB-CASE", RenameAll::ScreamingKebab),
+    ];
+
+    fn parse(lit: &LitStr) -> Result<Self, Error> {
+        let value = lit.value();
+        match Self::NAMES.iter().find(|n| n.0 == value) {
+            Some(&(_, rename_all)) => Ok(rename_all),
+            None => {
+                let names: Vec<_> = Self::NAMES.iter().map(|n| format!("`{}`", n.0)).collect();
+                let msg = format!("`rename_all` must be one of {}", names.join(", "));
+                Err(Error::new_spanned(lit, msg))
+            }
+        }
+    }
+
+    /// Converts a field name, which is assumed to be in snake case.
+    fn apply(self, field: &str) -> String {
+        match self {
+            RenameAll::Lower | RenameAll::Snake => field.to_ascii_lowercase(),
+            RenameAll::Upper | RenameAll::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameAll::Pascal | RenameAll::Camel => {
+                let mut res = String::with_capacity(field.len());
+                let mut upper = matches!(self, RenameAll::Pascal);
+                for c in field.chars() {
+                    if c == '_' {
+                        upper = true;
+                    } else if upper {
+                        res.push(c.to_ascii_uppercase());
+                        upper = false;
+                    } else {
+                        res.push(c);
+                    }
+                }
+                res
+            }
+            RenameAll::Kebab => field.replace('_', "-"),
+            RenameAll::ScreamingKebab => field.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
+
+pub struct ByPosition {
+    pub path: Path,
+    pub checked: bool,
+}
+
+pub fn get_columns_attr(attrs: &[Attribute]) -> Result<ColumnsAttr, Error> {
+    let mut cattr = ColumnsAttr::default();
+    for attr in attrs {
+        match &attr.meta {
+            Meta::Path(p) => assert_not_columns_attr(p)?,
+            Meta::NameValue(n) => assert_not_columns_attr(&n.path)?,
+            Meta::List(l) if l.path.is_ident(COLUMNS_ATTR) => {
+                cattr.span = Some(match cattr.span {
+                    None => l.tokens.span(),
+                    Some(s) => l.tokens.span().join(s).unwrap_or(s),
+                });
+                l.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("transparent") {
+                        if cattr.transparent {
+                            return Err(Error::new_spanned(
+                                meta.path,
+                                "`transparent` attribute specified multiple times",
+                            ));
+                        }
+                        cattr.transparent = true;
+                        Ok(())
+                    } else if meta.path.is_ident("case_insensitive") {
+                        if cattr.case_insensitive {
+                            return Err(Error::new_spanned(
+                                meta.path,
+                                "`case_insensitive` attribute specified multiple times",
+                            ));
+                        }
+                        cattr.case_insensitive = true;
+                        Ok(())
+                    } else if meta.path.is_ident("from_sql") {
+                        let path = meta.path.clone();
+                        set_once(&mut cattr.from_sql, &meta.path, "from_sql", path)
+                    } else if meta.path.is_ident("to_sql") {
+                        let path = meta.path.clone();
+                        set_once(&mut cattr.to_sql, &meta.path, "to_sql", path)
+                    } else if meta.path.is_ident("by_position") {
+                        let mut by_position = ByPosition {
+                            path: meta.path.clone(),
+                            checked: false,
+                        };
+                        if meta.input.peek(token::Paren) {
+                            meta.parse_nested_meta(|meta| {
+                                if meta.path.is_ident("checked") {
+                                    by_position.checked = true;
+                                    Ok(())
+                                } else {
+                                    Err(meta.error("Unknown attribute"))
+                                }
+                            })?;
+                        }
+                        set_once(
+                            &mut cattr.by_position,
+                            &meta.path,
+                            "by_position",
+                            by_position,
+                        )
+                    } else if meta.path.is_ident("remote") {
+                        let remote: Path = meta.value()?.parse::<LitStr>()?.parse()?;
+                        set_once(&mut cattr.remote, &meta.path, "remote", remote)
+                    } else if meta.path.is_ident("rename_all") {
+                        let rename_all = RenameAll::parse(&meta.value()?.parse()?)?;
+                        set_once(&mut cattr.rename_all, &meta.path, "rename_all", rename_all)
+                    } else {
+                        Err(meta.error("Unknown attribute"))
+                    }
+                })?;
+            }
+            Meta::List(_) => {}
+        }
+    }
+    for path in [&cattr.from_sql, &cattr.to_sql].into_iter().flatten() {
+        if !cattr.transparent {
+            let msg = format!("`{}` requires `transparent`", path.get_ident().unwrap());
+            return Err(Error::new_spanned(path, msg));
+        }
+        if cattr.remote.is_some() {
+            let msg = format!(
+                "`{}` cannot be combined with `remote`",
+                path.get_ident().unwrap()
+            );
+            return Err(Error::new_spanned(path, msg));
+        }
+    }
+    if let Some(by_position) = &cattr.by_position {
+        if cattr.transparent {
+            return Err(Error::new_spanned(
+                &by_position.path,
+                "`by_position` cannot be combined with `transparent`",
+            ));
+        }
+    }
+    Ok(cattr)
+}
+
+fn assert_not_columns_attr(path: &Path) -> Result<(), Error> {
+    if path.is_ident(COLUMNS_ATTR) {
+        let msg =
+            format!("`{COLUMNS_ATTR}` attribute must be a list attribute: `{COLUMNS_ATTR}()`");
+        return Err(Error::new_spanned(path, msg));
+    }
+    Ok(())
+}
+
 const COLUMN_ATTR: &str = "column";
 
 #[derive(Default)]
@@ -299,6 +1531,23 @@ struct ColumnAttr {
     span: Option<Span>,
     idx: Option<Expr>,
     name: Option<LitStr>,
+    with: Option<Path>,
+    with_row: Option<Path>,
+    via: Option<Type>,
+    from_columns: Option<Vec<LitStr>>,
+    pattern: Option<LitStr>,
+    range: Option<(u64, u64)>,
+    prefix_vec: Option<LitStr>,
+    rest: bool,
+    null_default: Option<NullDefault>,
+    aliases: Vec<LitStr>,
+    parse_with: Option<Path>,
+    sql_type: Option<Expr>,
+    key: bool,
+    primary_key: bool,
+    sort_key: bool,
+    skip: bool,
+    default: Option<Expr>,
 }
 
 fn get_column_attr(attrs: &[Attribute]) -> Result<ColumnAttr, Error> {
@@ -312,45 +1561,137 @@ fn get_column_attr(attrs: &[Attribute]) -> Result<ColumnAttr, Error> {
                     None => l.tokens.span(),
                     Some(s) => l.tokens.span().join(s).unwrap_or(s),
                 });
-                let values =
-                    Punctuated::<Meta, Token![,]>::parse_terminated.parse2(l.tokens.clone())?;
-                for meta in values {
-                    match meta {
-                        Meta::NameValue(n) => {
-                            if n.path.is_ident("idx") {
-                                if cattr.idx.is_some() {
-                                    return Err(Error::new_spanned(
-                                        n.path,
-                                        "`idx` attribute specified multiple times",
-                                    ));
-                                }
-                                cattr.idx = Some(n.value);
-                            } else if n.path.is_ident("name") {
-                                if cattr.name.is_some() {
-                                    return Err(Error::new_spanned(
-                                        n.path,
-                                        "`name` attribute specified multiple times",
-                                    ));
+                l.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("idx") {
+                        let idx = meta.value()?.parse()?;
+                        set_once(&mut cattr.idx, &meta.path, "idx", idx)
+                    } else if meta.path.is_ident("name") {
+                        let value: Expr = meta.value()?.parse()?;
+                        let name = 'name: {
+                            if let Expr::Lit(lit) = &value {
+                                if let Lit::Str(s) = &lit.lit {
+                                    break 'name s.clone();
                                 }
-                                let name = 'name: {
-                                    if let Expr::Lit(lit) = &n.value {
-                                        if let Lit::Str(s) = &lit.lit {
-                                            break 'name s.clone();
-                                        }
-                                    }
-                                    return Err(Error::new_spanned(
-                                        n.value,
-                                        "`name` attribute value must be a string literal",
-                                    ));
-                                };
-                                cattr.name = Some(name);
-                            } else {
-                                return Err(Error::new_spanned(n.path, "Unknown attribute"));
                             }
+                            return Err(Error::new_spanned(
+                                value,
+                                "`name` attribute value must be a string literal",
+                            ));
+                        };
+                        set_once(&mut cattr.name, &meta.path, "name", name)
+                    } else if meta.path.is_ident("alias") {
+                        cattr.aliases.push(meta.value()?.parse()?);
+                        Ok(())
+                    } else if meta.path.is_ident("parse_with") {
+                        let parse_with = meta.value()?.parse()?;
+                        set_once(&mut cattr.parse_with, &meta.path, "parse_with", parse_with)
+                    } else if meta.path.is_ident("sql_type") {
+                        let sql_type = meta.value()?.parse()?;
+                        set_once(&mut cattr.sql_type, &meta.path, "sql_type", sql_type)
+                    } else if meta.path.is_ident("with") {
+                        let with = meta.value()?.parse()?;
+                        set_once(&mut cattr.with, &meta.path, "with", with)
+                    } else if meta.path.is_ident("with_row") {
+                        let with_row = meta.value()?.parse()?;
+                        set_once(&mut cattr.with_row, &meta.path, "with_row", with_row)
+                    } else if meta.path.is_ident("from_columns") {
+                        let content;
+                        parenthesized!(content in meta.input);
+                        let names =
+                            content.parse_terminated(<LitStr as Parse>::parse, Token![,])?;
+                        if names.is_empty() {
+                            return Err(meta.error("`from_columns` requires at least one column"));
+                        }
+                        let names = names.into_iter().collect();
+                        set_once(&mut cattr.from_columns, &meta.path, "from_columns", names)
+                    } else if meta.path.is_ident("pattern") {
+                        let pattern = meta.value()?.parse()?;
+                        set_once(&mut cattr.pattern, &meta.path, "pattern", pattern)
+                    } else if meta.path.is_ident("range") {
+                        let input = meta.value()?;
+                        let start = input.parse::<LitInt>()?.base10_parse::<u64>()?;
+                        let inclusive = if input.peek(Token![..=]) {
+                            input.parse::<Token![..=]>()?;
+                            true
+                        } else {
+                            input.parse::<Token![..]>()?;
+                            false
+                        };
+                        let end = input.parse::<LitInt>()?;
+                        let end_value = end.base10_parse::<u64>()? + inclusive as u64;
+                        if end_value <= start {
+                            return Err(Error::new_spanned(end, "`range` must not be empty"));
+                        }
+                        set_once(&mut cattr.range, &meta.path, "range", (start, end_value))
+                    } else if meta.path.is_ident("prefix_vec") {
+                        let prefix = meta.value()?.parse()?;
+                        set_once(&mut cattr.prefix_vec, &meta.path, "prefix_vec", prefix)
+                    } else if meta.path.is_ident("skip") {
+                        if cattr.skip {
+                            return Err(Error::new_spanned(
+                                meta.path,
+                                "`skip` attribute specified multiple times",
+                            ));
                         }
-                        _ => return Err(Error::new_spanned(meta, "Unknown attribute")),
+                        cattr.skip = true;
+                        Ok(())
+                    } else if meta.path.is_ident("key") {
+                        if cattr.key {
+                            return Err(Error::new_spanned(
+                                meta.path,
+                                "`key` attribute specified multiple times",
+                            ));
+                        }
+                        cattr.key = true;
+                        Ok(())
+                    } else if meta.path.is_ident("primary_key") {
+                        if cattr.primary_key {
+                            return Err(Error::new_spanned(
+                                meta.path,
+                                "`primary_key` attribute specified multiple times",
+                            ));
+                        }
+                        cattr.primary_key = true;
+                        Ok(())
+                    } else if meta.path.is_ident("sort_key") {
+                        if cattr.sort_key {
+                            return Err(Error::new_spanned(
+                                meta.path,
+                                "`sort_key` attribute specified multiple times",
+                            ));
+                        }
+                        cattr.sort_key = true;
+                        Ok(())
+                    } else if meta.path.is_ident("default") {
+                        let default = meta.value()?.parse()?;
+                        set_once(&mut cattr.default, &meta.path, "default", default)
+                    } else if meta.path.is_ident("rest") {
+                        if cattr.rest {
+                            return Err(Error::new_spanned(
+                                meta.path,
+                                "`rest` attribute specified multiple times",
+                            ));
+                        }
+                        cattr.rest = true;
+                        Ok(())
+                    } else if meta.path.is_ident("null_default") {
+                        let null_default = match meta.input.peek(Token![=]) {
+                            true => NullDefault::Expr(meta.value()?.parse()?),
+                            false => NullDefault::Default,
+                        };
+                        set_once(
+                            &mut cattr.null_default,
+                            &meta.path,
+                            "null_default",
+                            null_default,
+                        )
+                    } else if meta.path.is_ident("via") {
+                        let via = meta.value()?.parse()?;
+                        set_once(&mut cattr.via, &meta.path, "via", via)
+                    } else {
+                        Err(meta.error("Unknown attribute"))
                     }
-                }
+                })?;
             }
             Meta::List(_) => {}
         }
@@ -358,6 +1699,15 @@ fn get_column_attr(attrs: &[Attribute]) -> Result<ColumnAttr, Error> {
     Ok(cattr)
 }
 
+pub fn set_once<T>(slot: &mut Option<T>, path: &Path, name: &str, value: T) -> Result<(), Error> {
+    if slot.is_some() {
+        let msg = format!("`{name}` attribute specified multiple times");
+        return Err(Error::new_spanned(path, msg));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
 fn assert_not_column_attr(path: &Path) -> Result<(), Error> {
     if path.is_ident(COLUMN_ATTR) {
         let msg = format!("`{COLUMN_ATTR}` attribute must be a list attribute: `{COLUMN_ATTR}()`");
@@ -0,0 +1,234 @@
+use {
+    crate::{
+        column::{
+            get_columns_attr, get_fields, named_columns, to_sql_columns, ColumnField, Decode,
+            NullDefault,
+        },
+        extract::get_extract_attr,
+    },
+    proc_macro2::TokenStream,
+    quote::quote,
+    std::collections::HashSet,
+    syn::{
+        parse_quote_spanned, spanned::Spanned, Data, DeriveInput, Error, Fields, Type,
+        WherePredicate,
+    },
+};
+
+pub fn copy_out_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`CopyOut` can only be derived for structs",
+            ))
+        }
+    };
+    let extract_attr = get_extract_attr(&input.attrs)?;
+    let rename_all = get_columns_attr(&input.attrs)?.rename_all;
+    let raw_fields: Vec<_> = str.fields.iter().collect();
+    let fields = match &str.fields {
+        Fields::Named(n) => get_fields(&n.named, rename_all)?,
+        Fields::Unnamed(u) => get_fields(&u.unnamed, rename_all)?,
+        Fields::Unit => vec![],
+    };
+    let mut names = vec![];
+    let mut types = vec![];
+    let mut values = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    let mut unique_types = HashSet::new();
+    let mut decoded_type = |ty: &Type, predicates: &mut Vec<WherePredicate>| {
+        if unique_types.insert(ty.clone()) {
+            predicates.push(parse_quote_spanned!(ty.span() =>
+                #ty: ::tokio_postgres_extractor::private::tokio_postgres::types::FromSqlOwned
+            ));
+        }
+    };
+    for (raw, field) in raw_fields.iter().zip(&fields) {
+        named_columns("CopyOut", raw, field, &mut names)?;
+        let ty = &raw.ty;
+        let slot = types.len();
+        let value = match &field.decode {
+            Decode::Get | Decode::Via(_) => {
+                let via = match &field.decode {
+                    Decode::Via(via) => Some(via),
+                    _ => None,
+                };
+                let decoded = via.unwrap_or(ty);
+                types.push(sql_type(field, decoded, &mut predicates));
+                if let Some(via) = via {
+                    predicates.push(parse_quote_spanned!(via.span() =>
+                        #ty: ::std::convert::TryFrom<#via>
+                    ));
+                    predicates.push(parse_quote_spanned!(via.span() =>
+                        <#ty as ::std::convert::TryFrom<#via>>::Error: ::std::fmt::Display
+                    ));
+                }
+                let convert = |value: TokenStream| match via {
+                    Some(via) => quote! {
+                        ::tokio_postgres_extractor::private::try_convert::<#ty, #via>(#slot, #value)?
+                    },
+                    None => value,
+                };
+                match &field.null_default {
+                    None => {
+                        decoded_type(decoded, &mut predicates);
+                        convert(quote!(row.try_get::<#decoded>(#slot)?))
+                    }
+                    Some(null_default) => {
+                        let option: Type = parse_quote_spanned!(decoded.span() =>
+                            ::std::option::Option<#decoded>
+                        );
+                        decoded_type(&option, &mut predicates);
+                        let convert = convert(quote!(value));
+                        let value = quote! {
+                            row.try_get::<#option>(#slot)?
+                                .map(|value| Ok::<_, ::tokio_postgres_extractor::Error>(#convert))
+                                .transpose()?
+                        };
+                        match null_default {
+                            NullDefault::Default => {
+                                predicates.push(parse_quote_spanned!(ty.span() =>
+                                    #ty: ::std::default::Default
+                                ));
+                                quote!(#value.unwrap_or_default())
+                            }
+                            NullDefault::Expr(e) => quote!(#value.unwrap_or_else(|| #e)),
+                        }
+                    }
+                }
+            }
+            Decode::Skip(Some(default)) => quote!(#default),
+            Decode::Skip(None) => {
+                predicates.push(parse_quote_spanned!(ty.span() =>
+                    #ty: ::std::default::Default
+                ));
+                quote!(::std::default::Default::default())
+            }
+            Decode::Array(elem) => {
+                decoded_type(elem, &mut predicates);
+                let slots: Vec<_> = (slot..slot + field.columns.len()).collect();
+                for _ in &slots {
+                    types.push(sql_type(field, elem, &mut predicates));
+                }
+                quote!([#(row.try_get::<#elem>(#slots)?,)*])
+            }
+            Decode::With(_) => {
+                return Err(Error::new_spanned(raw, "`CopyOut` does not support `with`"))
+            }
+            Decode::WithRow(_) => {
+                return Err(Error::new_spanned(
+                    raw,
+                    "`CopyOut` does not support `with_row`",
+                ))
+            }
+            Decode::Vec(_) | Decode::Rest(_) => unreachable!(),
+        };
+        match &raw.ident {
+            None => values.push(value),
+            Some(ident) => values.push(quote!(#ident: #value)),
+        }
+    }
+    let body = match &str.fields {
+        Fields::Named(_) => quote!(Self { #(#values,)* }),
+        Fields::Unnamed(_) => quote!(Self(#(#values),*)),
+        Fields::Unit => quote!(Self),
+    };
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let finish = extract_attr
+        .finish
+        .map(|finish| quote!(#finish(&mut value);));
+    let validate = extract_attr.validate.map(|validate| {
+        quote! {
+            ::tokio_postgres_extractor::private::try_validate(#name_str, #validate(&value))?;
+        }
+    });
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::CopyOut for #name #type_generics #where_clause {
+            const COLUMN_NAMES: &'static [&'static str] = &[#(#names,)*];
+
+            fn column_types() -> ::std::vec::Vec<::tokio_postgres_extractor::private::tokio_postgres::types::Type> {
+                ::std::vec![#(#types,)*]
+            }
+
+            fn extract_copy_out(
+                row: &::tokio_postgres_extractor::private::tokio_postgres::binary_copy::BinaryCopyOutRow,
+            ) -> ::std::result::Result<Self, ::tokio_postgres_extractor::Error> {
+                #[allow(unused_mut)]
+                let mut value = #body;
+                #finish
+                #validate
+                Ok(value)
+            }
+        }
+    })
+}
+
+pub fn copy_in_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`CopyIn` can only be derived for structs",
+            ))
+        }
+    };
+    let rename_all = get_columns_attr(&input.attrs)?.rename_all;
+    let raw_fields: Vec<_> = str.fields.iter().collect();
+    let fields = match &str.fields {
+        Fields::Named(n) => get_fields(&n.named, rename_all)?,
+        Fields::Unnamed(u) => get_fields(&u.unnamed, rename_all)?,
+        Fields::Unit => vec![],
+    };
+    let mut predicates = vec![];
+    let columns = to_sql_columns("CopyIn", &raw_fields, &fields, &mut predicates)?;
+    let names: Vec<_> = columns.iter().map(|c| c.name).collect();
+    let places: Vec<_> = columns.iter().map(|c| &c.place).collect();
+    let types: Vec<_> = columns
+        .iter()
+        .map(|c| sql_type(c.field, c.ty, &mut predicates))
+        .collect();
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::CopyIn for #name #type_generics #where_clause {
+            const COLUMN_NAMES: &'static [&'static str] = &[#(#names,)*];
+
+            fn column_types() -> ::std::vec::Vec<::tokio_postgres_extractor::private::tokio_postgres::types::Type> {
+                ::std::vec![#(#types,)*]
+            }
+
+            fn copy_in_values(
+                &self,
+            ) -> ::std::vec::Vec<&(dyn ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync)> {
+                ::std::vec![#(&self.#places,)*]
+            }
+        }
+    })
+}
+
+pub fn sql_type(
+    field: &ColumnField,
+    ty: &Type,
+    predicates: &mut Vec<WherePredicate>,
+) -> TokenStream {
+    match &field.sql_type {
+        Some(sql_type) => quote!(#sql_type),
+        None => {
+            predicates.push(parse_quote_spanned!(ty.span() =>
+                #ty: ::tokio_postgres_extractor::SqlType
+            ));
+            quote!(<#ty as ::tokio_postgres_extractor::SqlType>::sql_type())
+        }
+    }
+}
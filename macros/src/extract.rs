@@ -1,10 +1,12 @@
 use {
+    crate::column::{get_columns_attr, get_fields, set_once, Decode, NullDefault},
     proc_macro2::{Ident, Span, TokenStream},
-    quote::quote,
+    quote::{quote, ToTokens},
     std::collections::HashSet,
     syn::{
-        parse_quote_spanned, punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error,
-        Field, Fields, GenericParam, Lifetime, LifetimeParam, Token, Type, WhereClause,
+        parse_quote_spanned, punctuated::Punctuated, spanned::Spanned, Attribute, Data,
+        DeriveInput, Error, Field, Fields, GenericParam, Generics, Index, Lifetime, LifetimeParam,
+        Member, Meta, Path, PathArguments, Token, Type, WhereClause, WherePredicate,
     },
 };
 
@@ -18,58 +20,146 @@ pub fn extract_impl(input: DeriveInput) -> Result<TokenStream, Error> {
             ))
         }
     };
-    let (body, types) = match str.fields {
-        Fields::Named(named) => {
-            let (body, types) = fields(&named.named);
-            (quote!(Self { #(#body,)* }), types)
-        }
-        Fields::Unnamed(unnamed) => {
-            let (body, types) = fields(&unnamed.unnamed);
-            (quote!(Self(#(#body),*)), types)
-        }
-        Fields::Unit => (quote!(Self), vec![]),
-    };
     let (_, type_generics, _) = input.generics.split_for_impl();
     let mut modified_generics = input.generics.clone();
-    let lifetimes: Vec<_> = modified_generics
-        .params
+    let row_lt = fresh_lifetime(&input.generics, "row");
+    let extract_attr = get_extract_attr(&input.attrs)?;
+    let remote = get_columns_attr(&input.attrs)?.remote;
+    let members: Vec<_> = str
+        .fields
         .iter()
-        .filter_map(|l| match l {
-            GenericParam::Lifetime(l) => Some(l),
-            _ => None,
+        .enumerate()
+        .map(|(idx, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
         })
-        .map(|l| &l.lifetime)
         .collect();
-    let mut row_lt_name = String::new();
-    'outer: for idx in 0.. {
-        row_lt_name = format!("row{idx}");
-        for lt in &lifetimes {
-            if lt.ident == row_lt_name {
-                continue 'outer;
-            }
+    let (body, try_body, predicates) = match (str.fields, &remote) {
+        (Fields::Named(named), _) => {
+            let ExtractFields {
+                fields,
+                try_fields,
+                predicates,
+            } = fields(&named.named, &row_lt)?;
+            let ctor = match &remote {
+                Some(remote) => expr_path(remote),
+                None => quote!(Self),
+            };
+            (
+                quote!(#ctor { #(#fields,)* }),
+                quote!(#ctor { #(#try_fields,)* }),
+                predicates,
+            )
         }
-        break;
-    }
-    let row_lt = Lifetime {
-        apostrophe: Span::call_site(),
-        ident: Ident::new(&row_lt_name, Span::call_site()),
+        (Fields::Unnamed(unnamed), Some(remote)) => {
+            let ExtractFields {
+                fields,
+                try_fields,
+                predicates,
+            } = fields(&unnamed.unnamed, &row_lt)?;
+            let ctor = expr_path(remote);
+            let idx = (0..fields.len()).map(Index::from);
+            let try_idx = idx.clone();
+            (
+                quote!(#ctor { #(#idx: #fields,)* }),
+                quote!(#ctor { #(#try_idx: #try_fields,)* }),
+                predicates,
+            )
+        }
+        (Fields::Unnamed(unnamed), None) => {
+            let ExtractFields {
+                fields,
+                try_fields,
+                predicates,
+            } = fields(&unnamed.unnamed, &row_lt)?;
+            (
+                quote!(Self(#(#fields),*)),
+                quote!(Self(#(#try_fields),*)),
+                predicates,
+            )
+        }
+        (Fields::Unit, Some(remote)) => {
+            let ctor = expr_path(remote);
+            (quote!(#ctor {}), quote!(#ctor {}), vec![])
+        }
+        (Fields::Unit, None) => (quote!(Self), quote!(Self), vec![]),
     };
-    if types.len() > 0 {
+    let name = input.ident;
+    let name_str = name.to_string();
+    let (body, try_body) = match (&extract_attr.finish, &extract_attr.validate) {
+        (None, None) => (body, quote!(Ok(#try_body))),
+        (finish, validate) => {
+            let finish = finish.as_ref().map(|finish| quote!(#finish(&mut value);));
+            let (validate, try_validate) = match validate {
+                None => (None, None),
+                Some(validate) => (
+                    Some(quote! {
+                        ::tokio_postgres_extractor::private::validate(#name_str, #validate(&value));
+                    }),
+                    Some(quote! {
+                        ::tokio_postgres_extractor::private::try_validate(#name_str, #validate(&value))?;
+                    }),
+                ),
+            };
+            (
+                quote! {
+                    #[allow(unused_mut)]
+                    let mut value = #body;
+                    #finish
+                    #validate
+                    value
+                },
+                quote! {
+                    #[allow(unused_mut)]
+                    let mut value = #try_body;
+                    #finish
+                    #try_validate
+                    Ok(value)
+                },
+            )
+        }
+    };
+    if predicates.len() > 0 {
         let where_clause = modified_generics.where_clause.get_or_insert(WhereClause {
             where_token: Default::default(),
             predicates: Default::default(),
         });
-        for ty in &types {
-            where_clause.predicates.push(parse_quote_spanned!(
-                ty.span() => #ty: ::tokio_postgres_extractor::private::tokio_postgres::types::FromSql<#row_lt>
-            ))
-        }
+        where_clause.predicates.extend(predicates);
     }
     modified_generics
         .params
         .push(GenericParam::Lifetime(LifetimeParam::new(row_lt.clone())));
     let (impl_generics, _, where_clause) = modified_generics.split_for_impl();
-    let name = input.ident;
+    if let Some(remote) = remote {
+        let (orig_impl_generics, _, orig_where_clause) = input.generics.split_for_impl();
+        return Ok(quote! {
+            // The mirror is never constructed. Read its fields to avoid dead code warnings.
+            const _: () = {
+                #[allow(dead_code)]
+                fn read_fields #orig_impl_generics(value: &#name #type_generics) #orig_where_clause {
+                    #(let _ = &value.#members;)*
+                }
+            };
+
+
+            #[automatically_derived]
+            impl #impl_generics ::tokio_postgres_extractor::ExtractAs<#row_lt, #remote> for #name #type_generics #where_clause {
+                fn extract_as_with_columns(
+                    columns: &Self::Columns,
+                    row: &#row_lt ::tokio_postgres_extractor::private::tokio_postgres::Row,
+                ) -> #remote {
+                    #body
+                }
+
+                fn try_extract_as_with_columns(
+                    columns: &Self::Columns,
+                    row: &#row_lt ::tokio_postgres_extractor::private::tokio_postgres::Row,
+                ) -> ::std::result::Result<#remote, ::tokio_postgres_extractor::Error> {
+                    #try_body
+                }
+            }
+        });
+    }
     Ok(quote! {
         #[automatically_derived]
         impl #impl_generics ::tokio_postgres_extractor::Extract<#row_lt> for #name #type_generics #where_clause {
@@ -79,23 +169,261 @@ pub fn extract_impl(input: DeriveInput) -> Result<TokenStream, Error> {
             ) -> Self {
                 #body
             }
+
+            fn try_extract_with_columns(
+                columns: &Self::Columns,
+                row: &#row_lt ::tokio_postgres_extractor::private::tokio_postgres::Row,
+            ) -> ::std::result::Result<Self, ::tokio_postgres_extractor::Error> {
+                #try_body
+            }
         }
     })
 }
 
-fn fields(input: &Punctuated<Field, Token![,]>) -> (Vec<TokenStream>, Vec<Type>) {
+struct ExtractFields {
+    fields: Vec<TokenStream>,
+    try_fields: Vec<TokenStream>,
+    predicates: Vec<WherePredicate>,
+}
+
+fn fields(input: &Punctuated<Field, Token![,]>, row_lt: &Lifetime) -> Result<ExtractFields, Error> {
     let mut fields = vec![];
+    let mut try_fields = vec![];
     let mut unique_types = HashSet::new();
-    let mut types = vec![];
-    for (idx, field) in input.iter().enumerate() {
+    let mut predicates = vec![];
+    let mut from_sql = |ty: &Type, predicates: &mut Vec<WherePredicate>| {
+        if unique_types.insert(ty.clone()) {
+            predicates.push(parse_quote_spanned!(
+                ty.span() => #ty: ::tokio_postgres_extractor::private::tokio_postgres::types::FromSql<#row_lt>
+            ));
+        }
+    };
+    let mut offset = 0;
+    let mut dynamic_idx = 0usize;
+    // The names of the columns are only used by the mapping.
+    for (field, column) in input.iter().zip(get_fields(input, None)?) {
+        let idx = offset;
+        let slots = offset..offset + column.columns.len();
+        offset = slots.end;
         let get = quote!(row.get(columns[#idx]));
-        fields.push(match &field.ident {
-            None => get,
-            Some(ident) => quote!(#ident: #get),
-        });
-        if unique_types.insert(field.ty.clone()) {
-            types.push(field.ty.clone());
+        let try_get = quote!(row.try_get(columns[#idx])?);
+        let (get, try_get) = match column.decode {
+            Decode::Get | Decode::Via(_) => {
+                let ty = &field.ty;
+                let via = match &column.decode {
+                    Decode::Via(via) => Some(via),
+                    _ => None,
+                };
+                let decoded = via.unwrap_or(ty);
+                let (mut get, mut try_get) = match column.null_default {
+                    None => {
+                        from_sql(decoded, &mut predicates);
+                        (get, try_get)
+                    }
+                    Some(_) => {
+                        let option: Type = parse_quote_spanned!(
+                            decoded.span() => ::std::option::Option<#decoded>
+                        );
+                        from_sql(&option, &mut predicates);
+                        (
+                            quote!(row.get::<_, #option>(columns[#idx])),
+                            quote!(row.try_get::<_, #option>(columns[#idx])?),
+                        )
+                    }
+                };
+                if let Some(via) = via {
+                    let convert = quote!(::tokio_postgres_extractor::private::convert::<#ty, #via>);
+                    let try_convert =
+                        quote!(::tokio_postgres_extractor::private::try_convert::<#ty, #via>);
+                    (get, try_get) = match column.null_default {
+                        None => (
+                            quote!(#convert(columns[#idx], #get)),
+                            quote!(#try_convert(columns[#idx], #try_get)?),
+                        ),
+                        Some(_) => (
+                            quote!(#get.map(|v| #convert(columns[#idx], v))),
+                            quote!(#try_get.map(|v| #try_convert(columns[#idx], v)).transpose()?),
+                        ),
+                    };
+                    predicates.push(parse_quote_spanned!(
+                        ty.span() => #ty: ::std::convert::TryFrom<#via>
+                    ));
+                    predicates.push(parse_quote_spanned!(
+                        ty.span() => <#ty as ::std::convert::TryFrom<#via>>::Error: ::std::fmt::Display
+                    ));
+                }
+                match &column.null_default {
+                    None => {}
+                    Some(NullDefault::Default) => {
+                        predicates.push(parse_quote_spanned!(
+                            ty.span() => #ty: ::std::default::Default
+                        ));
+                        get = quote!(#get.unwrap_or_default());
+                        try_get = quote!(#try_get.unwrap_or_default());
+                    }
+                    Some(NullDefault::Expr(e)) => {
+                        get = quote!(#get.unwrap_or_else(|| #e));
+                        try_get = quote!(#try_get.unwrap_or_else(|| #e));
+                    }
+                }
+                (get, try_get)
+            }
+            Decode::Skip(default) => {
+                let default = match default {
+                    Some(default) => default.into_token_stream(),
+                    None => {
+                        let ty = &field.ty;
+                        predicates.push(parse_quote_spanned!(
+                            ty.span() => #ty: ::std::default::Default
+                        ));
+                        quote!(::std::default::Default::default())
+                    }
+                };
+                (default.clone(), default)
+            }
+            Decode::With(with) => {
+                let slots: Vec<_> = slots.collect();
+                (
+                    quote!(#with(#(row.get(columns[#slots])),*)),
+                    quote!(#with(#(row.try_get(columns[#slots])?),*)),
+                )
+            }
+            Decode::WithRow(with_row) => {
+                let value = quote!(#with_row(row, columns[#idx]));
+                (
+                    quote!(::tokio_postgres_extractor::private::with_row(#value)),
+                    quote!(#value?),
+                )
+            }
+            Decode::Array(elem) => {
+                from_sql(&elem, &mut predicates);
+                let slots: Vec<_> = slots.collect();
+                (
+                    quote!([#(row.get(columns[#slots])),*]),
+                    quote!([#(row.try_get(columns[#slots])?),*]),
+                )
+            }
+            Decode::Vec(elem) => {
+                from_sql(&elem, &mut predicates);
+                let idx = dynamic_idx;
+                dynamic_idx += 1;
+                (
+                    quote! {
+                        columns.dynamic[#idx].iter().map(|&idx| row.get(idx)).collect()
+                    },
+                    quote! {
+                        columns.dynamic[#idx]
+                            .iter()
+                            .map(|&idx| row.try_get(idx))
+                            .collect::<::std::result::Result<_, _>>()?
+                    },
+                )
+            }
+            Decode::Rest(value) => {
+                from_sql(&value, &mut predicates);
+                let idx = dynamic_idx;
+                dynamic_idx += 1;
+                (
+                    quote! {
+                        columns.dynamic[#idx]
+                            .iter()
+                            .map(|&idx| (row.columns()[idx].name().to_owned(), row.get(idx)))
+                            .collect()
+                    },
+                    quote! {
+                        columns.dynamic[#idx]
+                            .iter()
+                            .map(|&idx| Ok((row.columns()[idx].name().to_owned(), row.try_get(idx)?)))
+                            .collect::<::std::result::Result<_, ::tokio_postgres_extractor::Error>>()?
+                    },
+                )
+            }
+        };
+        match &field.ident {
+            None => {
+                fields.push(get);
+                try_fields.push(try_get);
+            }
+            Some(ident) => {
+                fields.push(quote!(#ident: #get));
+                try_fields.push(quote!(#ident: #try_get));
+            }
+        }
+    }
+    Ok(ExtractFields {
+        fields,
+        try_fields,
+        predicates,
+    })
+}
+
+/// Returns the path in expression position, i.e., with turbofish generic arguments.
+fn expr_path(path: &Path) -> TokenStream {
+    let mut path = path.clone();
+    for segment in &mut path.segments {
+        if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+            args.colon2_token = Some(Default::default());
+        }
+    }
+    path.into_token_stream()
+}
+
+pub fn fresh_lifetime(generics: &Generics, prefix: &str) -> Lifetime {
+    let lifetimes: Vec<_> = generics.lifetimes().map(|l| &l.lifetime).collect();
+    let mut name = String::new();
+    'outer: for idx in 0.. {
+        name = format!("{prefix}{idx}");
+        for lt in &lifetimes {
+            if lt.ident == name {
+                continue 'outer;
+            }
+        }
+        break;
+    }
+    Lifetime {
+        apostrophe: Span::call_site(),
+        ident: Ident::new(&name, Span::call_site()),
+    }
+}
+
+const EXTRACT_ATTR: &str = "extract";
+
+#[derive(Default)]
+pub struct ExtractAttr {
+    pub validate: Option<Path>,
+    pub finish: Option<Path>,
+}
+
+pub fn get_extract_attr(attrs: &[Attribute]) -> Result<ExtractAttr, Error> {
+    let mut eattr = ExtractAttr::default();
+    for attr in attrs {
+        match &attr.meta {
+            Meta::Path(p) => assert_not_extract_attr(p)?,
+            Meta::NameValue(n) => assert_not_extract_attr(&n.path)?,
+            Meta::List(l) if l.path.is_ident(EXTRACT_ATTR) => {
+                l.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("validate") {
+                        let validate = meta.value()?.parse()?;
+                        set_once(&mut eattr.validate, &meta.path, "validate", validate)
+                    } else if meta.path.is_ident("finish") {
+                        let finish = meta.value()?.parse()?;
+                        set_once(&mut eattr.finish, &meta.path, "finish", finish)
+                    } else {
+                        Err(meta.error("Unknown attribute"))
+                    }
+                })?;
+            }
+            Meta::List(_) => {}
         }
     }
-    (fields, types)
+    Ok(eattr)
+}
+
+fn assert_not_extract_attr(path: &Path) -> Result<(), Error> {
+    if path.is_ident(EXTRACT_ATTR) {
+        let msg =
+            format!("`{EXTRACT_ATTR}` attribute must be a list attribute: `{EXTRACT_ATTR}()`");
+        return Err(Error::new_spanned(path, msg));
+    }
+    Ok(())
 }
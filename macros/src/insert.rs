@@ -0,0 +1,61 @@
+use {
+    crate::{
+        column::{get_columns_attr, get_fields, to_sql_columns},
+        extract::fresh_lifetime,
+    },
+    proc_macro2::TokenStream,
+    quote::quote,
+    syn::{Data, DeriveInput, Error, Fields},
+};
+
+pub fn insert_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`Insert` can only be derived for structs",
+            ))
+        }
+    };
+    let rename_all = get_columns_attr(&input.attrs)?.rename_all;
+    let raw_fields: Vec<_> = str.fields.iter().collect();
+    let fields = match &str.fields {
+        Fields::Named(n) => get_fields(&n.named, rename_all)?,
+        Fields::Unnamed(u) => get_fields(&u.unnamed, rename_all)?,
+        Fields::Unit => vec![],
+    };
+    let mut predicates = vec![];
+    let columns = to_sql_columns("Insert", &raw_fields, &fields, &mut predicates)?;
+    if columns.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`Insert` requires at least one column",
+        ));
+    }
+    let names = columns.iter().map(|c| c.name);
+    let keys = columns.iter().filter(|c| c.field.key).map(|c| c.name);
+    let places = columns.iter().map(|c| &c.place);
+    let num_columns = columns.len();
+    let name = &input.ident;
+    let lt = fresh_lifetime(&input.generics, "params");
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::Insert for #name #type_generics #where_clause {
+            const COLUMN_NAMES: &'static [&'static str] = &[#(#names,)*];
+
+            const KEY_COLUMNS: &'static [&'static str] = &[#(#keys,)*];
+
+            type Params<#lt> = [&#lt (dyn ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync); #num_columns]
+            where
+                Self: #lt;
+
+            fn params(&self) -> Self::Params<'_> {
+                [#(&self.#places,)*]
+            }
+        }
+    })
+}
@@ -1,15 +1,34 @@
 #![allow(clippy::len_zero)]
 
 use {
-    crate::{column::columns_impl, extract::extract_impl},
+    crate::{
+        changeset::changeset_impl,
+        column::columns_impl,
+        copy::{copy_in_impl, copy_out_impl},
+        extract::extract_impl,
+        insert::insert_impl,
+        page::paginate_impl,
+        params::{named_query_impl, params_impl, NamedQuery},
+        table::table_impl,
+        text::extract_text_impl,
+        unnest::unnest_impl,
+    },
     proc_macro::TokenStream,
     syn::{parse_macro_input, DeriveInput},
 };
 
+mod changeset;
 mod column;
+mod copy;
 mod extract;
+mod insert;
+mod page;
+mod params;
+mod table;
+mod text;
+mod unnest;
 
-#[proc_macro_derive(Columns, attributes(column))]
+#[proc_macro_derive(Columns, attributes(column, columns))]
 pub fn columns(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     columns_impl(input)
@@ -17,10 +36,90 @@ pub fn columns(input: TokenStream) -> TokenStream {
         .into()
 }
 
-#[proc_macro_derive(Extract)]
+#[proc_macro_derive(Extract, attributes(column, columns, extract))]
 pub fn extract(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     extract_impl(input)
         .unwrap_or_else(|e| e.into_compile_error())
         .into()
 }
+
+#[proc_macro_derive(ExtractText, attributes(column, columns, extract))]
+pub fn extract_text(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    extract_text_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(CopyOut, attributes(column, columns, extract))]
+pub fn copy_out(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    copy_out_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(CopyIn, attributes(column, columns))]
+pub fn copy_in(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    copy_in_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(Insert, attributes(column, columns))]
+pub fn insert(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    insert_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(Changeset, attributes(column, columns, changeset))]
+pub fn changeset(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    changeset_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(Table, attributes(column, columns, table))]
+pub fn table(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    table_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(Paginate, attributes(column, columns))]
+pub fn paginate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    paginate_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(Params, attributes(column, columns))]
+pub fn params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    params_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(Unnest, attributes(column, columns))]
+pub fn unnest(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    unnest_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+#[proc_macro]
+pub fn named_query(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as NamedQuery);
+    named_query_impl(input)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
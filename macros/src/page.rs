@@ -0,0 +1,83 @@
+use {
+    crate::{
+        column::{get_columns_attr, get_fields, named_columns, Decode},
+        copy::sql_type,
+    },
+    proc_macro2::TokenStream,
+    quote::quote,
+    syn::{
+        parse_quote_spanned, spanned::Spanned, Data, DeriveInput, Error, Fields, Index, Member,
+        WherePredicate,
+    },
+};
+
+pub fn paginate_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`Paginate` can only be derived for structs",
+            ))
+        }
+    };
+    let rename_all = get_columns_attr(&input.attrs)?.rename_all;
+    let raw_fields: Vec<_> = str.fields.iter().collect();
+    let fields = match &str.fields {
+        Fields::Named(n) => get_fields(&n.named, rename_all)?,
+        Fields::Unnamed(u) => get_fields(&u.unnamed, rename_all)?,
+        Fields::Unit => vec![],
+    };
+    let mut names = vec![];
+    let mut types = vec![];
+    let mut values = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    for (idx, (raw, field)) in raw_fields.iter().zip(&fields).enumerate() {
+        if !field.sort_key {
+            continue;
+        }
+        if !matches!(field.decode, Decode::Get) {
+            return Err(Error::new_spanned(
+                raw,
+                "`sort_key` requires a field that maps to a single column",
+            ));
+        }
+        named_columns("Paginate", raw, field, &mut names)?;
+        let ty = &raw.ty;
+        predicates.push(parse_quote_spanned!(ty.span() =>
+            #ty: ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync
+        ));
+        types.push(sql_type(field, ty, &mut predicates));
+        let member = match &raw.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        };
+        values.push(quote!(&self.#member));
+    }
+    if names.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`Paginate` requires at least one field annotated with `#[column(sort_key)]`",
+        ));
+    }
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::Paginate for #name #type_generics #where_clause {
+            const SORT_KEYS: &'static [&'static str] = &[#(#names,)*];
+
+            fn sort_key_types() -> ::std::vec::Vec<::tokio_postgres_extractor::private::tokio_postgres::types::Type> {
+                ::std::vec![#(#types,)*]
+            }
+
+            fn sort_key_values(
+                &self,
+            ) -> ::std::vec::Vec<&(dyn ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync)> {
+                ::std::vec![#(#values,)*]
+            }
+        }
+    })
+}
@@ -0,0 +1,87 @@
+use {
+    crate::column::{get_columns_attr, get_fields, to_sql_columns},
+    proc_macro2::TokenStream,
+    quote::quote,
+    syn::{
+        parse::{Parse, ParseStream},
+        Data, DeriveInput, Error, Fields, LitStr, Token, Type,
+    },
+    tokio_postgres_extractor_internal::rewrite_named_params,
+};
+
+pub fn params_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`Params` can only be derived for structs",
+            ))
+        }
+    };
+    let rename_all = get_columns_attr(&input.attrs)?.rename_all;
+    let raw_fields: Vec<_> = str.fields.iter().collect();
+    let fields = match &str.fields {
+        Fields::Named(n) => get_fields(&n.named, rename_all)?,
+        Fields::Unnamed(u) => get_fields(&u.unnamed, rename_all)?,
+        Fields::Unit => vec![],
+    };
+    let mut predicates = vec![];
+    let columns = to_sql_columns("Params", &raw_fields, &fields, &mut predicates)?;
+    let names = columns.iter().map(|c| c.name);
+    let places = columns.iter().map(|c| &c.place);
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::Params for #name #type_generics #where_clause {
+            const NAMES: &'static [&'static str] = &[#(#names,)*];
+
+            fn values(
+                &self,
+            ) -> ::std::vec::Vec<&(dyn ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync)> {
+                ::std::vec![#(&self.#places,)*]
+            }
+        }
+    })
+}
+
+pub struct NamedQuery {
+    ty: Type,
+    sql: LitStr,
+}
+
+impl Parse for NamedQuery {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let sql = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { ty, sql })
+    }
+}
+
+pub fn named_query_impl(input: NamedQuery) -> Result<TokenStream, Error> {
+    let NamedQuery { ty, sql } = input;
+    let value = sql.value();
+    let (rewritten, names) = rewrite_named_params(&value);
+    let rewritten = LitStr::new(&rewritten, sql.span());
+    let ty_str = quote!(#ty).to_string();
+    let indices = names.iter().map(|name| {
+        let msg = format!("`{}` has no parameter named `{}`", ty_str, name);
+        quote! {{
+            let idx = ::tokio_postgres_extractor::private::param_index(
+                <#ty as ::tokio_postgres_extractor::Params>::NAMES,
+                #name,
+            );
+            ::std::assert!(idx != usize::MAX, #msg);
+            idx
+        }}
+    });
+    Ok(quote! {{
+        const INDICES: &[usize] = &[#(#indices,)*];
+        ::tokio_postgres_extractor::private::named_query::<#ty>(#rewritten, INDICES)
+    }})
+}
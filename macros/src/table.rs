@@ -0,0 +1,124 @@
+use {
+    crate::column::{get_columns_attr, get_fields, named_columns, set_once, Decode},
+    proc_macro2::TokenStream,
+    quote::quote,
+    syn::{
+        parse_quote_spanned, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Index,
+        LitStr, Member, Meta, Path, WherePredicate,
+    },
+};
+
+pub fn table_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`Table` can only be derived for structs",
+            ))
+        }
+    };
+    let table_attr = get_table_attr(&input.attrs)?;
+    let Some(table) = table_attr.name else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`Table` requires the table name, use `#[table(name = \"...\")]`",
+        ));
+    };
+    let rename_all = get_columns_attr(&input.attrs)?.rename_all;
+    let raw_fields: Vec<_> = str.fields.iter().collect();
+    let fields = match &str.fields {
+        Fields::Named(n) => get_fields(&n.named, rename_all)?,
+        Fields::Unnamed(u) => get_fields(&u.unnamed, rename_all)?,
+        Fields::Unit => vec![],
+    };
+    let mut primary_key = None;
+    for (idx, (raw, field)) in raw_fields.iter().zip(&fields).enumerate() {
+        if !field.primary_key {
+            continue;
+        }
+        if primary_key.is_some() {
+            return Err(Error::new_spanned(
+                raw,
+                "`primary_key` can only be specified for a single field",
+            ));
+        }
+        if !matches!(field.decode, Decode::Get) {
+            return Err(Error::new_spanned(
+                raw,
+                "`primary_key` requires a field that maps to a single column",
+            ));
+        }
+        let mut names = vec![];
+        named_columns("Table", raw, field, &mut names)?;
+        let member = match &raw.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        };
+        primary_key = Some((names[0], &raw.ty, member));
+    }
+    let Some((key_column, key_ty, key_member)) = primary_key else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`Table` requires a field annotated with `#[column(primary_key)]`",
+        ));
+    };
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    let predicate: WherePredicate = parse_quote_spanned!(key_ty.span() =>
+        #key_ty: ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync
+    );
+    generics.make_where_clause().predicates.push(predicate);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::Table for #name #type_generics #where_clause {
+            const TABLE: &'static str = #table;
+
+            const PRIMARY_KEY: &'static str = #key_column;
+
+            type Key = #key_ty;
+
+            fn key(&self) -> &Self::Key {
+                &self.#key_member
+            }
+        }
+    })
+}
+
+const TABLE_ATTR: &str = "table";
+
+#[derive(Default)]
+struct TableAttr {
+    name: Option<LitStr>,
+}
+
+fn get_table_attr(attrs: &[Attribute]) -> Result<TableAttr, Error> {
+    let mut tattr = TableAttr::default();
+    for attr in attrs {
+        match &attr.meta {
+            Meta::Path(p) => assert_not_table_attr(p)?,
+            Meta::NameValue(n) => assert_not_table_attr(&n.path)?,
+            Meta::List(l) if l.path.is_ident(TABLE_ATTR) => {
+                l.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        let name = meta.value()?.parse()?;
+                        set_once(&mut tattr.name, &meta.path, "name", name)
+                    } else {
+                        Err(meta.error("Unknown attribute"))
+                    }
+                })?;
+            }
+            Meta::List(_) => {}
+        }
+    }
+    Ok(tattr)
+}
+
+fn assert_not_table_attr(path: &Path) -> Result<(), Error> {
+    if path.is_ident(TABLE_ATTR) {
+        let msg = format!("`{TABLE_ATTR}` attribute must be a list attribute: `{TABLE_ATTR}()`");
+        return Err(Error::new_spanned(path, msg));
+    }
+    Ok(())
+}
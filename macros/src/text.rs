@@ -0,0 +1,212 @@
+use {
+    crate::{
+        column::{generic_args, get_columns_attr, get_fields, mapping, Decode, NullDefault},
+        extract::get_extract_attr,
+    },
+    proc_macro2::TokenStream,
+    quote::quote,
+    std::collections::HashSet,
+    syn::{
+        parse_quote_spanned, punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error,
+        Field, Fields, Token, Type, WherePredicate,
+    },
+};
+
+pub fn extract_text_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`ExtractText` can only be derived for structs",
+            ))
+        }
+    };
+    if let Some(remote) = get_columns_attr(&input.attrs)?.remote {
+        return Err(Error::new_spanned(
+            remote,
+            "`remote` is not supported by `ExtractText`",
+        ));
+    }
+    let extract_attr = get_extract_attr(&input.attrs)?;
+    let mapping = mapping(&input)?.body;
+    let (body, predicates) = match &str.fields {
+        Fields::Named(named) => {
+            let (fields, predicates) = fields(&named.named)?;
+            (quote!(Self { #(#fields,)* }), predicates)
+        }
+        Fields::Unnamed(unnamed) => {
+            let (fields, predicates) = fields(&unnamed.unnamed)?;
+            (quote!(Self(#(#fields),*)), predicates)
+        }
+        Fields::Unit => (quote!(Self), vec![]),
+    };
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let finish = extract_attr
+        .finish
+        .map(|finish| quote!(#finish(&mut value);));
+    let validate = extract_attr.validate.map(|validate| {
+        quote! {
+            ::tokio_postgres_extractor::private::try_validate(#name_str, #validate(&value))?;
+        }
+    });
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::ExtractText for #name #type_generics #where_clause {
+            fn try_text_columns(
+                row: &impl ::tokio_postgres_extractor::text::TextRow,
+            ) -> ::std::result::Result<Self::Columns, ::tokio_postgres_extractor::Error> {
+                let row = ::tokio_postgres_extractor::private::TextColumns::new(row);
+                #mapping
+            }
+
+            #[allow(clippy::needless_question_mark)]
+            fn extract_text_with_columns(
+                columns: &Self::Columns,
+                row: &impl ::tokio_postgres_extractor::text::TextRow,
+            ) -> ::std::result::Result<Self, ::tokio_postgres_extractor::Error> {
+                #[allow(unused_mut)]
+                let mut value = #body;
+                #finish
+                #validate
+                Ok(value)
+            }
+        }
+    })
+}
+
+fn fields(
+    input: &Punctuated<Field, Token![,]>,
+) -> Result<(Vec<TokenStream>, Vec<WherePredicate>), Error> {
+    let mut fields = vec![];
+    let mut unique_types = HashSet::new();
+    let mut predicates = vec![];
+    let mut offset = 0;
+    let mut dynamic_idx = 0usize;
+    // The names of the columns are only used by the mapping.
+    for (field, column) in input.iter().zip(get_fields(input, None)?) {
+        let idx = offset;
+        offset += column.columns.len();
+        let ty = &field.ty;
+        let mut parse = |decoded: &Type, nullable: bool, predicates: &mut Vec<WherePredicate>| {
+            let (decoded, optional) = match generic_args(decoded, "Option") {
+                Some(args) if nullable && args.len() == 1 => (args[0], true),
+                _ => (decoded, false),
+            };
+            let parser = match &column.parse_with {
+                Some(path) => quote!(#path),
+                None => {
+                    if unique_types.insert(decoded.clone()) {
+                        predicates.push(parse_quote_spanned!(decoded.span() =>
+                            #decoded: ::std::str::FromStr
+                        ));
+                        predicates.push(parse_quote_spanned!(decoded.span() =>
+                            <#decoded as ::std::str::FromStr>::Err: ::std::convert::Into<::std::boxed::Box<dyn ::std::error::Error + ::std::marker::Send + ::std::marker::Sync>>
+                        ));
+                    }
+                    quote!(<#decoded as ::std::str::FromStr>::from_str)
+                }
+            };
+            let value = quote! {
+                ::tokio_postgres_extractor::private::parse_text(column, row.get(column), #parser)?
+            };
+            (value, optional)
+        };
+        let non_null = |value: TokenStream, optional: bool| match optional {
+            true => value,
+            false => quote!(::tokio_postgres_extractor::private::text_non_null(column, #value)?),
+        };
+        let value = match &column.decode {
+            Decode::Get | Decode::Via(_) => {
+                let via = match &column.decode {
+                    Decode::Via(via) => Some(via),
+                    _ => None,
+                };
+                let nullable = via.is_none() && column.null_default.is_none();
+                let (mut value, optional) = parse(via.unwrap_or(ty), nullable, &mut predicates);
+                if let Some(via) = via {
+                    predicates.push(parse_quote_spanned!(via.span() =>
+                        #ty: ::std::convert::TryFrom<#via>
+                    ));
+                    predicates.push(parse_quote_spanned!(via.span() =>
+                        <#ty as ::std::convert::TryFrom<#via>>::Error: ::std::fmt::Display
+                    ));
+                    value = quote! {
+                        #value
+                            .map(|value| ::tokio_postgres_extractor::private::try_convert::<#ty, #via>(column, value))
+                            .transpose()?
+                    };
+                }
+                let value = match &column.null_default {
+                    None => non_null(value, optional),
+                    Some(NullDefault::Default) => {
+                        predicates.push(parse_quote_spanned!(ty.span() =>
+                            #ty: ::std::default::Default
+                        ));
+                        quote!(#value.unwrap_or_default())
+                    }
+                    Some(NullDefault::Expr(e)) => quote!(#value.unwrap_or_else(|| #e)),
+                };
+                quote! {{
+                    let column = columns[#idx];
+                    #value
+                }}
+            }
+            Decode::Skip(Some(default)) => quote!(#default),
+            Decode::Skip(None) => {
+                predicates.push(parse_quote_spanned!(ty.span() =>
+                    #ty: ::std::default::Default
+                ));
+                quote!(::std::default::Default::default())
+            }
+            Decode::With(_) => {
+                return Err(Error::new_spanned(
+                    field,
+                    "`with` is not supported by `ExtractText`, use `parse_with` instead",
+                ))
+            }
+            Decode::WithRow(_) => {
+                return Err(Error::new_spanned(
+                    field,
+                    "`with_row` is not supported by `ExtractText`, use `parse_with` instead",
+                ))
+            }
+            Decode::Array(elem) => {
+                let (value, optional) = parse(elem, true, &mut predicates);
+                let value = non_null(value, optional);
+                let slots = idx..offset;
+                quote! {
+                    [#({
+                        let column = columns[#slots];
+                        #value
+                    },)*]
+                }
+            }
+            Decode::Vec(elem) | Decode::Rest(elem) => {
+                let (value, optional) = parse(elem, true, &mut predicates);
+                let value = non_null(value, optional);
+                let item = match &column.decode {
+                    Decode::Rest(_) => quote!((row.column_name(column).to_owned(), #value)),
+                    _ => value,
+                };
+                let k = dynamic_idx;
+                dynamic_idx += 1;
+                quote! {
+                    columns.dynamic[#k]
+                        .iter()
+                        .map(|&column| Ok(#item))
+                        .collect::<::std::result::Result<_, ::tokio_postgres_extractor::Error>>()?
+                }
+            }
+        };
+        match &field.ident {
+            None => fields.push(value),
+            Some(ident) => fields.push(quote!(#ident: #value)),
+        }
+    }
+    Ok((fields, predicates))
+}
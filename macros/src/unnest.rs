@@ -0,0 +1,67 @@
+use {
+    crate::{
+        column::{get_columns_attr, get_fields, to_sql_columns},
+        copy::sql_type,
+    },
+    proc_macro2::TokenStream,
+    quote::quote,
+    syn::{parse_quote_spanned, spanned::Spanned, Data, DeriveInput, Error, Fields},
+};
+
+pub fn unnest_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let str = match &input.data {
+        Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "`Unnest` can only be derived for structs",
+            ))
+        }
+    };
+    let rename_all = get_columns_attr(&input.attrs)?.rename_all;
+    let raw_fields: Vec<_> = str.fields.iter().collect();
+    let fields = match &str.fields {
+        Fields::Named(n) => get_fields(&n.named, rename_all)?,
+        Fields::Unnamed(u) => get_fields(&u.unnamed, rename_all)?,
+        Fields::Unit => vec![],
+    };
+    let mut predicates = vec![];
+    let columns = to_sql_columns("Unnest", &raw_fields, &fields, &mut predicates)?;
+    let names: Vec<_> = columns.iter().map(|c| c.name).collect();
+    let places: Vec<_> = columns.iter().map(|c| &c.place).collect();
+    let mut types = vec![];
+    for column in &columns {
+        if column.field.sql_type.is_none() {
+            // Postgres does not have arrays of arrays.
+            let ty = column.ty;
+            predicates.push(parse_quote_spanned!(ty.span() =>
+                #ty: ::tokio_postgres_extractor::SqlArrayType
+            ));
+        }
+        types.push(sql_type(column.field, column.ty, &mut predicates));
+    }
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_postgres_extractor::Unnest for #name #type_generics #where_clause {
+            const COLUMN_NAMES: &'static [&'static str] = &[#(#names,)*];
+
+            fn column_types() -> ::std::vec::Vec<::tokio_postgres_extractor::private::tokio_postgres::types::Type> {
+                ::std::vec![#(#types,)*]
+            }
+
+            fn column_arrays(
+                rows: &[Self],
+            ) -> ::std::vec::Vec<::std::boxed::Box<dyn ::tokio_postgres_extractor::private::tokio_postgres::types::ToSql + ::std::marker::Sync + '_>> {
+                ::std::vec![#(
+                    ::std::boxed::Box::new(
+                        rows.iter().map(|row| &row.#places).collect::<::std::vec::Vec<_>>(),
+                    ),
+                )*]
+            }
+        }
+    })
+}